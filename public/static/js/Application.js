@@ -5,18 +5,52 @@ define(["knockout", "reqwest"], function(ko, reqwest) {
 		this.query = ko.observable("");
 		this.fields = ko.observableArray([]);
 		this.results = ko.observableArray([]);
+		this.total = ko.observable(0);
+		this.offset = ko.observable(0);
+		this.limit = 50;
+		this.sort = ko.observable(null);
 		this.isLoading = ko.observable(false);
 		this.isError = ko.observable(false);
 		this.errorMessage = ko.observable("");
+		this.problems = ko.observableArray([]);
+		this.facets = ko.observableArray([]);
+		this.suggestions = ko.observableArray([]);
+		this.suggestedQuery = ko.observable(null);
 
 		this.isResultsVisible = ko.pureComputed(function() {
 			return this.results().length > 0;
 		}, this);
 
+		this.firstResult = ko.pureComputed(function() {
+			return this.offset() + 1;
+		}, this);
+
+		this.lastResult = ko.pureComputed(function() {
+			return this.offset() + this.results().length;
+		}, this);
+
+		this.hasPreviousPage = ko.pureComputed(function() {
+			return this.offset() > 0;
+		}, this);
+
+		this.hasNextPage = ko.pureComputed(function() {
+			return this.offset() + this.results().length < this.total();
+		}, this);
+
+		this.isProblemsVisible = ko.pureComputed(function() {
+			return this.problems().length > 0 && !this.isError();
+		}, this);
+
 		this.isHelpVisible = ko.pureComputed(function() {
 			return this.fields().length > 0 && this.results().length === 0;
 		}, this);
 
+		this.isSortedBy = function(field, order) {
+			const sort = this.sort();
+
+			return sort !== null && sort.field === field.name && sort.order === order;
+		};
+
 		this.isNumericField = function(field) {
 			return field.data_type === "number";
 		};
@@ -25,6 +59,10 @@ define(["knockout", "reqwest"], function(ko, reqwest) {
 			return field.data_type === "string";
 		};
 
+		this.query.extend({ rateLimit: { timeout: 300, method: "notifyWhenChangesStop" } });
+		this.query.subscribe(this.validateQuery, this);
+		this.query.subscribe(this.updateSuggestions, this);
+
 		this.updateFileds();
 	};
 
@@ -36,23 +74,102 @@ define(["knockout", "reqwest"], function(ko, reqwest) {
 		}
 	};
 
+	// Replaces values of matched text fields with highlighted snippets.
+	Application.prototype.highlightHits = function(hits, highlights) {
+		return hits.map(function(hit, index) {
+			return Object.assign({}, hit, highlights[index] || {});
+		});
+	};
+
+	// Joins facet counts of response with display names of fields.
+	Application.prototype.facetCounts = function(facets) {
+		return this.fields()
+			.filter(function(field) {
+				return field.name in facets;
+			})
+			.map(function(field) {
+				return { display: field.display, values: facets[field.name] };
+			});
+	};
+
+	Application.prototype.facetFields = function() {
+		return this.fields()
+			.filter(function(field) {
+				return field.facet;
+			})
+			.map(function(field) {
+				return field.name;
+			});
+	};
+
+	Application.prototype.exportUrl = function(format) {
+		return "/api/v1/export?format=" + format + "&query=" + encodeURIComponent(this.query());
+	};
+
 	Application.prototype.sendRequest = function() {
+		this.loadPage(0);
+	};
+
+	Application.prototype.searchSuggestedQuery = function() {
+		this.query(this.suggestedQuery());
+		this.loadPage(0);
+	};
+
+	Application.prototype.previousPage = function() {
+		this.loadPage(Math.max(this.offset() - this.limit, 0));
+	};
+
+	Application.prototype.sortBy = function(field) {
+		if (!field.sortable) {
+			return;
+		}
+
+		const sort = this.sort();
+
+		if (sort === null || sort.field !== field.name) {
+			this.sort({ field: field.name, order: "desc" });
+		} else if (sort.order === "desc") {
+			this.sort({ field: field.name, order: "asc" });
+		} else {
+			this.sort(null);
+		}
+
+		this.loadPage(0);
+	};
+
+	Application.prototype.nextPage = function() {
+		this.loadPage(this.offset() + this.limit);
+	};
+
+	Application.prototype.loadPage = function(offset) {
 		reqwest({
 			url: "/api/v1/search",
 			type: "json",
 			method: "POST",
 			contentType: "application/json",
-			data: JSON.stringify({ query: this.query() }),
+			data: JSON.stringify({
+				query: this.query(),
+				sort: this.sort() === null ? [] : [this.sort()],
+				offset: offset,
+				limit: this.limit,
+				highlight: { fragment_size: 200, pre_tag: "<mark>", post_tag: "</mark>" },
+				facets: this.facetFields(),
+			}),
 		})
 			.then(
 				function(resp) {
 					if (resp.success) {
 						this.isError(false);
-						this.results(resp.result);
+						this.total(resp.result.total);
+						this.offset(resp.result.offset);
+						this.results(this.highlightHits(resp.result.hits, resp.result.highlights || []));
+						this.facets(this.facetCounts(resp.result.facets || {}));
+						this.suggestedQuery(resp.result.suggested_query || null);
 					} else {
 						this.isError(true);
 						this.errorMessage(resp.message);
 						this.results([]);
+						this.suggestedQuery(null);
 					}
 
 					this.isLoading(false);
@@ -70,6 +187,73 @@ define(["knockout", "reqwest"], function(ko, reqwest) {
 		this.isLoading(true);
 	};
 
+	Application.prototype.validateQuery = function() {
+		reqwest({
+			url: "/api/v1/parse",
+			type: "json",
+			method: "POST",
+			contentType: "application/json",
+			data: JSON.stringify({ query: this.query() }),
+		})
+			.then(
+				function(resp) {
+					if (resp.success) {
+						this.problems(resp.result.problems);
+					} else {
+						this.problems([]);
+					}
+				}.bind(this)
+			)
+			.fail(
+				function() {
+					this.problems([]);
+				}.bind(this)
+			);
+	};
+
+	// Suggestions complete the last word of query, field name is kept for terms of one field.
+	Application.prototype.updateSuggestions = function() {
+		const query = this.query();
+		const match = /[^\s()+-]*$/.exec(query);
+		const word = match[0];
+		const head = query.slice(0, match.index);
+		const field = word.slice(0, word.indexOf(":") + 1);
+
+		if (word === "") {
+			this.suggestions([]);
+
+			return;
+		}
+
+		reqwest({
+			url: "/api/v1/suggest?prefix=" + encodeURIComponent(word),
+			type: "json",
+			method: "GET",
+		})
+			.then(
+				function(resp) {
+					if (resp.success) {
+						const terms = resp.result.terms.map(function(term) {
+							return field + term.value;
+						});
+
+						this.suggestions(
+							resp.result.fields.concat(terms).map(function(value) {
+								return head + value;
+							})
+						);
+					} else {
+						this.suggestions([]);
+					}
+				}.bind(this)
+			)
+			.fail(
+				function() {
+					this.suggestions([]);
+				}.bind(this)
+			);
+	};
+
 	Application.prototype.updateFileds = function() {
 		reqwest({
 			url: "/api/v1/fields",
@@ -0,0 +1,287 @@
+use super::FastField;
+use super::FastValueReader;
+use super::Presence;
+use std::collections::BTreeMap;
+use tantivy::collector::Collector;
+use tantivy::collector::SegmentCollector;
+use tantivy::DocId;
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::SegmentLocalId;
+use tantivy::SegmentReader;
+use tantivy::Term;
+
+#[derive(Debug, Clone)]
+pub enum AggregationKind {
+    Histogram(i128),
+    // Ranges include lower bound and exclude upper bound.
+    Range(Vec<(Option<i128>, Option<i128>)>),
+    Stats,
+}
+
+// Fast fields contain zero for documents without value, so only documents containing `present` term are aggregated.
+#[derive(Debug, Clone)]
+pub struct Aggregation {
+    field: FastField,
+    present: Term,
+    kind: AggregationKind,
+}
+
+impl Aggregation {
+    pub fn new(field: FastField, present: Term, kind: AggregationKind) -> Aggregation {
+        Aggregation {
+            field,
+            present,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AggregationFruit {
+    Histogram(BTreeMap<i128, u64>),
+    Range(Vec<u64>),
+    Stats(Stats),
+}
+
+impl AggregationFruit {
+    fn new(kind: &AggregationKind) -> AggregationFruit {
+        match kind {
+            AggregationKind::Histogram(..) => AggregationFruit::Histogram(BTreeMap::new()),
+            AggregationKind::Range(ranges) => AggregationFruit::Range(vec![0; ranges.len()]),
+            AggregationKind::Stats => AggregationFruit::Stats(Stats::default()),
+        }
+    }
+
+    fn add(&mut self, kind: &AggregationKind, value: i128) {
+        match (self, kind) {
+            (AggregationFruit::Histogram(buckets), &AggregationKind::Histogram(interval)) => {
+                let key = value.div_euclid(interval) * interval;
+
+                *buckets.entry(key).or_insert(0) += 1;
+            }
+            (AggregationFruit::Range(counts), AggregationKind::Range(ranges)) => {
+                for (count, &range) in counts.iter_mut().zip(ranges) {
+                    match range {
+                        (Some(from), _) if value < from => {}
+                        (_, Some(to)) if value >= to => {}
+                        _ => *count += 1,
+                    }
+                }
+            }
+            (AggregationFruit::Stats(stats), AggregationKind::Stats) => stats.add(value),
+            _ => {}
+        }
+    }
+
+    fn merge(&mut self, other: AggregationFruit) {
+        match (self, other) {
+            (AggregationFruit::Histogram(buckets), AggregationFruit::Histogram(other)) => {
+                for (key, count) in other {
+                    *buckets.entry(key).or_insert(0) += count;
+                }
+            }
+            (AggregationFruit::Range(counts), AggregationFruit::Range(other)) => {
+                for (count, other) in counts.iter_mut().zip(other) {
+                    *count += other;
+                }
+            }
+            (AggregationFruit::Stats(stats), AggregationFruit::Stats(other)) => stats.merge(&other),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Stats {
+    count: u64,
+    min: Option<i128>,
+    max: Option<i128>,
+    sum: i128,
+}
+
+impl Stats {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Option<i128> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<i128> {
+        self.max
+    }
+
+    pub fn sum(&self) -> i128 {
+        self.sum
+    }
+
+    fn add(&mut self, value: i128) {
+        self.count += 1;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        self.sum += value;
+    }
+
+    fn merge(&mut self, other: &Stats) {
+        self.count += other.count;
+        self.min = self.min.into_iter().chain(other.min).min();
+        self.max = self.max.into_iter().chain(other.max).max();
+        self.sum += other.sum;
+    }
+}
+
+// Computes all aggregations in one pass, fruit contains results in order of given aggregations.
+#[derive(Debug)]
+pub struct AggregationCollector {
+    aggregations: Vec<Aggregation>,
+}
+
+impl AggregationCollector {
+    pub fn new(aggregations: Vec<Aggregation>) -> AggregationCollector {
+        AggregationCollector { aggregations }
+    }
+}
+
+impl Collector for AggregationCollector {
+    type Fruit = Vec<AggregationFruit>;
+    type Child = AggregationSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<AggregationSegmentCollector> {
+        let mut aggregations = Vec::new();
+
+        for aggregation in &self.aggregations {
+            aggregations.push(SegmentAggregation {
+                reader: aggregation.field.reader(segment)?,
+                presence: Presence::new(segment, &aggregation.present),
+                kind: aggregation.kind.clone(),
+                fruit: AggregationFruit::new(&aggregation.kind),
+            });
+        }
+
+        Ok(AggregationSegmentCollector { aggregations })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<AggregationFruit>>,
+    ) -> Result<Vec<AggregationFruit>> {
+        let mut result: Vec<_> = self
+            .aggregations
+            .iter()
+            .map(|aggregation| AggregationFruit::new(&aggregation.kind))
+            .collect();
+
+        for segment_fruit in segment_fruits {
+            for (fruit, segment_fruit) in result.iter_mut().zip(segment_fruit) {
+                fruit.merge(segment_fruit);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+pub struct AggregationSegmentCollector {
+    aggregations: Vec<SegmentAggregation>,
+}
+
+struct SegmentAggregation {
+    reader: FastValueReader,
+    presence: Presence,
+    kind: AggregationKind,
+    fruit: AggregationFruit,
+}
+
+impl SegmentCollector for AggregationSegmentCollector {
+    type Fruit = Vec<AggregationFruit>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        for aggregation in &mut self.aggregations {
+            if aggregation.presence.contains(doc) {
+                let value = aggregation.reader.get(doc);
+
+                aggregation.fruit.add(&aggregation.kind, value);
+            }
+        }
+    }
+
+    fn harvest(self) -> Vec<AggregationFruit> {
+        self.aggregations
+            .into_iter()
+            .map(|aggregation| aggregation.fruit)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aggregate(kind: &AggregationKind, values: &[i128]) -> AggregationFruit {
+        let mut fruit = AggregationFruit::new(kind);
+
+        for &value in values {
+            fruit.add(kind, value);
+        }
+
+        fruit
+    }
+
+    #[test]
+    fn should_add_negative_values_to_histogram_buckets() {
+        let kind = AggregationKind::Histogram(10);
+        let fruit = aggregate(&kind, &[-21, -20, -11, -10, -1, 0, 9, 10]);
+
+        match fruit {
+            AggregationFruit::Histogram(buckets) => assert_eq!(
+                buckets.into_iter().collect::<Vec<_>>(),
+                vec![(-30, 1), (-20, 2), (-10, 2), (0, 2), (10, 1)]
+            ),
+            _ => panic!("Histogram expected"),
+        }
+    }
+
+    #[test]
+    fn should_add_values_to_ranges() {
+        let kind = AggregationKind::Range(vec![
+            (None, Some(-10)),
+            (Some(-10), Some(10)),
+            (Some(10), None),
+        ]);
+        let fruit = aggregate(&kind, &[-11, -10, 0, 9, 10]);
+
+        match fruit {
+            AggregationFruit::Range(counts) => assert_eq!(counts, vec![1, 3, 1]),
+            _ => panic!("Range expected"),
+        }
+    }
+
+    #[test]
+    fn should_merge_stats() {
+        let kind = AggregationKind::Stats;
+        let mut fruit = aggregate(&kind, &[-5, 3]);
+
+        fruit.merge(aggregate(&kind, &[]));
+        fruit.merge(aggregate(&kind, &[7]));
+
+        match fruit {
+            AggregationFruit::Stats(stats) => {
+                assert_eq!(stats.count(), 3);
+                assert_eq!(stats.min(), Some(-5));
+                assert_eq!(stats.max(), Some(7));
+                assert_eq!(stats.sum(), 5);
+            }
+            _ => panic!("Stats expected"),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use tantivy::collector::Collector;
+use tantivy::collector::SegmentCollector;
+use tantivy::fastfield::FacetReader;
+use tantivy::schema::Facet;
+use tantivy::schema::Field;
+use tantivy::DocId;
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::SegmentLocalId;
+use tantivy::SegmentReader;
+
+// Counts values of single level facet fields, fruit contains counts in order of given fields.
+#[derive(Debug)]
+pub struct FacetCountCollector {
+    fields: Vec<Field>,
+}
+
+impl FacetCountCollector {
+    pub fn new(fields: Vec<Field>) -> FacetCountCollector {
+        FacetCountCollector { fields }
+    }
+}
+
+impl Collector for FacetCountCollector {
+    type Fruit = Vec<HashMap<String, u64>>;
+    type Child = FacetCountSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<FacetCountSegmentCollector> {
+        let mut readers = Vec::new();
+
+        for &field in &self.fields {
+            let reader = segment.facet_reader(field)?;
+            let counts = vec![0; reader.num_facets()];
+
+            readers.push((reader, counts));
+        }
+
+        Ok(FacetCountSegmentCollector {
+            readers,
+            ords: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<HashMap<String, u64>>>,
+    ) -> Result<Vec<HashMap<String, u64>>> {
+        let mut result = vec![HashMap::new(); self.fields.len()];
+
+        for segment_fruit in segment_fruits {
+            for (counts, segment_counts) in result.iter_mut().zip(segment_fruit) {
+                for (value, count) in segment_counts {
+                    *counts.entry(value).or_insert(0) += count;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+// Values are counted by term ordinals of segment and resolved only once on harvest.
+pub struct FacetCountSegmentCollector {
+    readers: Vec<(FacetReader, Vec<u64>)>,
+    ords: Vec<u64>,
+}
+
+impl SegmentCollector for FacetCountSegmentCollector {
+    type Fruit = Vec<HashMap<String, u64>>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        for (reader, counts) in &mut self.readers {
+            reader.facet_ords(doc, &mut self.ords);
+
+            for &ord in &self.ords {
+                counts[ord as usize] += 1;
+            }
+        }
+    }
+
+    fn harvest(self) -> Vec<HashMap<String, u64>> {
+        let mut result = Vec::new();
+
+        for (mut reader, counts) in self.readers {
+            let mut values = HashMap::new();
+            let mut facet = Facet::root();
+
+            for (ord, count) in counts.into_iter().enumerate() {
+                if count > 0 && reader.facet_from_ord(ord as u64, &mut facet).is_ok() {
+                    values.insert(facet.encoded_str().to_string(), count);
+                }
+            }
+
+            result.push(values);
+        }
+
+        result
+    }
+}
@@ -0,0 +1,94 @@
+use tantivy::fastfield::FastFieldReader;
+use tantivy::postings::SegmentPostings;
+use tantivy::schema::Field;
+use tantivy::schema::IndexRecordOption;
+use tantivy::DocId;
+use tantivy::DocSet;
+use tantivy::Result;
+use tantivy::SegmentReader;
+use tantivy::SkipResult;
+use tantivy::Term;
+
+#[derive(Debug, Clone, Copy)]
+pub enum FastField {
+    I64(Field),
+    U64(Field),
+}
+
+impl FastField {
+    pub fn reader(self, segment: &SegmentReader) -> Result<FastValueReader> {
+        Ok(match self {
+            FastField::I64(field) => FastValueReader::I64(segment.fast_field_reader(field)?),
+            FastField::U64(field) => FastValueReader::U64(segment.fast_field_reader(field)?),
+        })
+    }
+
+    pub fn field(self) -> Field {
+        match self {
+            FastField::I64(field) | FastField::U64(field) => field,
+        }
+    }
+}
+
+// Values of both signed and unsigned fields are read as `i128` to compare them without overflow.
+pub enum FastValueReader {
+    I64(FastFieldReader<i64>),
+    U64(FastFieldReader<u64>),
+}
+
+impl FastValueReader {
+    pub fn get(&self, doc: DocId) -> i128 {
+        match self {
+            FastValueReader::I64(reader) => i128::from(reader.get(doc)),
+            FastValueReader::U64(reader) => i128::from(reader.get(doc)),
+        }
+    }
+}
+
+// Documents are collected in increasing order, so postings of present term are walked only once.
+pub struct Presence {
+    postings: Option<SegmentPostings>,
+    current: Option<DocId>,
+}
+
+impl Presence {
+    pub fn new(segment: &SegmentReader, present: &Term) -> Presence {
+        Presence {
+            postings: segment
+                .inverted_index(present.field())
+                .read_postings(present, IndexRecordOption::Basic),
+            current: None,
+        }
+    }
+
+    pub fn contains(&mut self, doc: DocId) -> bool {
+        if let Some(current) = self.current {
+            if current >= doc {
+                return current == doc;
+            }
+        }
+
+        let postings = match &mut self.postings {
+            Some(postings) => postings,
+            None => return false,
+        };
+
+        match postings.skip_next(doc) {
+            SkipResult::Reached => {
+                self.current = Some(doc);
+
+                true
+            }
+            SkipResult::OverStep => {
+                self.current = Some(postings.doc());
+
+                false
+            }
+            SkipResult::End => {
+                self.postings = None;
+
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use tantivy::collector::Collector;
+use tantivy::collector::SegmentCollector;
+use tantivy::fastfield::FastFieldReader;
+use tantivy::schema::Field;
+use tantivy::DocAddress;
+use tantivy::DocId;
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::SegmentLocalId;
+use tantivy::SegmentReader;
+
+// Collects addresses of all matched documents ordered by key, documents are not read.
+#[derive(Debug)]
+pub struct KeyCollector {
+    key_field: Field,
+}
+
+impl KeyCollector {
+    pub fn new(key_field: Field) -> KeyCollector {
+        KeyCollector { key_field }
+    }
+}
+
+impl Collector for KeyCollector {
+    type Fruit = Vec<(i64, DocAddress)>;
+    type Child = KeySegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<KeySegmentCollector> {
+        Ok(KeySegmentCollector {
+            segment_local_id,
+            key_reader: segment.fast_field_reader(self.key_field)?,
+            keys: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(i64, DocAddress)>>,
+    ) -> Result<Vec<(i64, DocAddress)>> {
+        let mut result: Vec<_> = segment_fruits.into_iter().flatten().collect();
+
+        result.sort_unstable_by_key(|&(key, _)| key);
+
+        Ok(result)
+    }
+}
+
+pub struct KeySegmentCollector {
+    segment_local_id: SegmentLocalId,
+    key_reader: FastFieldReader<i64>,
+    keys: Vec<(i64, DocAddress)>,
+}
+
+impl SegmentCollector for KeySegmentCollector {
+    type Fruit = Vec<(i64, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let key = self.key_reader.get(doc);
+
+        self.keys
+            .push((key, DocAddress(self.segment_local_id, doc)));
+    }
+
+    fn harvest(self) -> Vec<(i64, DocAddress)> {
+        self.keys
+    }
+}
@@ -0,0 +1,18 @@
+mod aggregation;
+mod facet;
+mod field;
+mod key;
+mod sort;
+
+pub use self::aggregation::Aggregation;
+pub use self::aggregation::AggregationCollector;
+pub use self::aggregation::AggregationFruit;
+pub use self::aggregation::AggregationKind;
+pub use self::facet::FacetCountCollector;
+pub use self::field::FastField;
+pub use self::field::FastValueReader;
+pub use self::field::Presence;
+pub use self::key::KeyCollector;
+pub use self::sort::SortCollector;
+pub use self::sort::SortOrder;
+pub use self::sort::SortPosition;
@@ -0,0 +1,315 @@
+use super::FastField;
+use super::FastValueReader;
+use super::Presence;
+use crate::index::PRESENT_FIELD;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use tantivy::collector::Collector;
+use tantivy::collector::SegmentCollector;
+use tantivy::fastfield::FastFieldReader;
+use tantivy::schema::Field;
+use tantivy::DocAddress;
+use tantivy::DocId;
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::SegmentLocalId;
+use tantivy::SegmentReader;
+use tantivy::Term;
+
+// Values of fast fields are at most 64 bits, so this position is after every real value.
+const MISSING_VALUE: i128 = i128::MAX;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+// Collects best documents ordered by fast field values, equal documents are ordered by score and key.
+#[derive(Debug)]
+pub struct SortCollector {
+    fields: Vec<(FastField, SortOrder)>,
+    key_field: Field,
+    after: Option<SortPosition>,
+    limit: usize,
+}
+
+impl SortCollector {
+    pub fn new(
+        fields: Vec<(FastField, SortOrder)>,
+        key_field: Field,
+        limit: usize,
+    ) -> SortCollector {
+        SortCollector {
+            fields,
+            key_field,
+            after: None,
+            limit,
+        }
+    }
+
+    // Only documents placed after given position are collected.
+    pub fn after(self, position: SortPosition) -> SortCollector {
+        SortCollector {
+            after: Some(position),
+            ..self
+        }
+    }
+}
+
+impl Collector for SortCollector {
+    type Fruit = Vec<SortedDoc>;
+    type Child = SortSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<SortSegmentCollector> {
+        let schema = segment.schema();
+        let present_field = schema.get_field(PRESENT_FIELD);
+        let mut readers = Vec::new();
+
+        for &(field, order) in &self.fields {
+            let presence = present_field.map(|present_field| {
+                let name = schema.get_field_name(field.field());
+
+                Presence::new(segment, &Term::from_field_text(present_field, name))
+            });
+
+            readers.push((field.reader(segment)?, presence, order));
+        }
+
+        Ok(SortSegmentCollector {
+            segment_local_id,
+            readers,
+            key_reader: segment.fast_field_reader(self.key_field)?,
+            after: self.after.clone(),
+            heap: BinaryHeap::new(),
+            limit: self.limit,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<SortedDoc>>) -> Result<Vec<SortedDoc>> {
+        let mut heap = BinaryHeap::new();
+
+        for doc in segment_fruits.into_iter().flatten() {
+            push_limited(&mut heap, doc, self.limit);
+        }
+
+        Ok(heap.into_sorted_vec())
+    }
+}
+
+pub struct SortSegmentCollector {
+    segment_local_id: SegmentLocalId,
+    readers: Vec<(FastValueReader, Option<Presence>, SortOrder)>,
+    key_reader: FastFieldReader<i64>,
+    after: Option<SortPosition>,
+    heap: BinaryHeap<SortedDoc>,
+    limit: usize,
+}
+
+impl SegmentCollector for SortSegmentCollector {
+    type Fruit = Vec<SortedDoc>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let values = self
+            .readers
+            .iter_mut()
+            .map(|(reader, presence, order)| {
+                // Fast fields contain zero for null values, so documents without value go last in both orders.
+                if let Some(presence) = presence {
+                    if !presence.contains(doc) {
+                        return MISSING_VALUE;
+                    }
+                }
+
+                let value = reader.get(doc);
+
+                match order {
+                    SortOrder::Asc => value,
+                    SortOrder::Desc => -value,
+                }
+            })
+            .collect();
+        let position = SortPosition {
+            values,
+            score,
+            key: self.key_reader.get(doc),
+        };
+
+        if let Some(after) = &self.after {
+            if position <= *after {
+                return;
+            }
+        }
+
+        let doc = SortedDoc {
+            position,
+            address: DocAddress(self.segment_local_id, doc),
+        };
+
+        push_limited(&mut self.heap, doc, self.limit);
+    }
+
+    fn harvest(self) -> Vec<SortedDoc> {
+        self.heap.into_vec()
+    }
+}
+
+// Descending values are negated, so smaller position is always better.
+#[derive(Debug, Clone)]
+pub struct SortPosition {
+    values: Vec<i128>,
+    score: Score,
+    key: i64,
+}
+
+impl SortPosition {
+    pub fn new(values: Vec<i128>, score: Score, key: i64) -> SortPosition {
+        SortPosition { values, score, key }
+    }
+
+    pub fn values(&self) -> &[i128] {
+        &self.values
+    }
+
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
+    pub fn key(&self) -> i64 {
+        self.key
+    }
+}
+
+impl Ord for SortPosition {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.values
+            .cmp(&other.values)
+            .then_with(|| {
+                other
+                    .score
+                    .partial_cmp(&self.score)
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| self.key.cmp(&other.key))
+    }
+}
+
+impl PartialOrd for SortPosition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortPosition {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortPosition {}
+
+#[derive(Debug)]
+pub struct SortedDoc {
+    position: SortPosition,
+    address: DocAddress,
+}
+
+impl SortedDoc {
+    pub fn position(&self) -> &SortPosition {
+        &self.position
+    }
+
+    pub fn address(&self) -> DocAddress {
+        self.address
+    }
+}
+
+impl Ord for SortedDoc {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.position
+            .cmp(&other.position)
+            .then_with(|| self.address.cmp(&other.address))
+    }
+}
+
+impl PartialOrd for SortedDoc {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortedDoc {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortedDoc {}
+
+// Heap top contains the worst document, so it is replaced when better one is found.
+fn push_limited(heap: &mut BinaryHeap<SortedDoc>, doc: SortedDoc, limit: usize) {
+    if heap.len() < limit {
+        heap.push(doc);
+    } else if let Some(mut worst) = heap.peek_mut() {
+        if doc < *worst {
+            *worst = doc;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+    use tantivy::query::AllQuery;
+
+    fn sorted_keys(order: SortOrder) -> Vec<i64> {
+        let index = create_index(&[
+            &[("id", "1"), ("size", "-5")],
+            &[("id", "2")],
+            &[("id", "3"), ("size", "3")],
+            &[("id", "4"), ("size", "-1")],
+            &[("id", "5")],
+            &[("id", "6"), ("size", "0")],
+        ]);
+
+        index
+            .text_index
+            .read(|reader, schema| {
+                let collector = SortCollector::new(
+                    vec![(FastField::I64(schema["size"]), order)],
+                    schema["id"],
+                    10,
+                );
+
+                reader
+                    .searcher()
+                    .search(&AllQuery, &collector)
+                    .unwrap()
+                    .iter()
+                    .map(|sorted_doc| sorted_doc.position().key())
+                    .collect()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn should_sort_missing_values_last_in_ascending_order() {
+        assert_eq!(sorted_keys(SortOrder::Asc), vec![1, 4, 6, 3, 2, 5]);
+    }
+
+    #[test]
+    fn should_sort_missing_values_last_in_descending_order() {
+        assert_eq!(sorted_keys(SortOrder::Desc), vec![3, 6, 4, 1, 2, 5]);
+    }
+}
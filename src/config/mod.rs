@@ -19,6 +19,8 @@ pub struct Config {
     interval: u64,
     datasource: DatasourceConfig,
     schema: Vec<FieldConfig>,
+    #[serde(default)]
+    search: SearchConfig,
 }
 
 impl Config {
@@ -41,6 +43,17 @@ impl Config {
     pub fn schema(&self) -> &[FieldConfig] {
         &self.schema
     }
+
+    pub fn search(&self) -> &SearchConfig {
+        &self.search
+    }
+
+    // Field which contains value of datasource key column.
+    pub fn key_field(&self) -> Option<&FieldConfig> {
+        let key = self.datasource.key();
+
+        self.schema.iter().find(|field| field.column() == key)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -84,6 +97,68 @@ impl DatasourceConfig {
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    max_expansions: usize,
+    default_limit: usize,
+    max_limit: usize,
+    max_offset: usize,
+    cursor_timeout: u64,
+    facet_size: usize,
+    similar_terms: usize,
+    suggest_size: usize,
+}
+
+impl SearchConfig {
+    pub fn max_expansions(&self) -> usize {
+        self.max_expansions
+    }
+
+    pub fn default_limit(&self) -> usize {
+        self.default_limit
+    }
+
+    pub fn max_limit(&self) -> usize {
+        self.max_limit
+    }
+
+    pub fn max_offset(&self) -> usize {
+        self.max_offset
+    }
+
+    pub fn cursor_timeout(&self) -> u64 {
+        self.cursor_timeout
+    }
+
+    pub fn facet_size(&self) -> usize {
+        self.facet_size
+    }
+
+    pub fn similar_terms(&self) -> usize {
+        self.similar_terms
+    }
+
+    pub fn suggest_size(&self) -> usize {
+        self.suggest_size
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            max_expansions: 1024,
+            default_limit: 50,
+            max_limit: 1000,
+            max_offset: 10000,
+            cursor_timeout: 600,
+            facet_size: 10,
+            similar_terms: 25,
+            suggest_size: 10,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FieldConfig {
     name: String,
@@ -91,6 +166,8 @@ pub struct FieldConfig {
     display: String,
     description: String,
     data_type: DataType,
+    #[serde(default)]
+    facet: bool,
 }
 
 impl FieldConfig {
@@ -113,13 +190,25 @@ impl FieldConfig {
     pub fn data_type(&self) -> DataType {
         self.data_type
     }
+
+    pub fn facet(&self) -> bool {
+        self.facet
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, Copy)]
 #[serde(tag = "type")]
 pub enum DataType {
-    Int { indexed: bool },
-    UInt { indexed: bool },
+    Int {
+        indexed: bool,
+        #[serde(default)]
+        sortable: bool,
+    },
+    UInt {
+        indexed: bool,
+        #[serde(default)]
+        sortable: bool,
+    },
     Text,
 }
 
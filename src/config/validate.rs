@@ -1,14 +1,20 @@
 use super::Config;
 use super::ConfigError;
 use super::ConfigResult;
+use super::DataType;
+use super::FieldConfig;
+use super::SearchConfig;
 
 use std::path::Path;
 
 #[allow(clippy::needless_pass_by_value)]
 pub fn validate(config: &Config) -> ConfigResult<()> {
     validate_number(config.interval(), "interval")?;
+    validate_search(config.search())?;
     validate_dir(config.index_path(), "index path")?;
     validate_file(config.state_file(), "state file")?;
+    validate_fields(config.schema())?;
+    validate_key(config)?;
 
     Ok(())
 }
@@ -24,6 +30,53 @@ fn validate_number(value: u64, name: &str) -> ConfigResult<()> {
     }
 }
 
+fn validate_search(search: &SearchConfig) -> ConfigResult<()> {
+    validate_number(search.max_expansions() as u64, "max expansions")?;
+    validate_number(search.default_limit() as u64, "default limit")?;
+    validate_number(search.max_limit() as u64, "max limit")?;
+    validate_number(search.cursor_timeout(), "cursor timeout")?;
+    validate_number(search.facet_size() as u64, "facet size")?;
+    validate_number(search.similar_terms() as u64, "similar terms")?;
+    validate_number(search.suggest_size() as u64, "suggest size")?;
+
+    if search.default_limit() > search.max_limit() {
+        return Err(ConfigError::format(format_args!(
+            "Default limit {} must not be greater than max limit {}",
+            search.default_limit(),
+            search.max_limit()
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_fields(schema: &[FieldConfig]) -> ConfigResult<()> {
+    for field in schema {
+        if field.name().starts_with("__") {
+            return Err(ConfigError::format(format_args!(
+                "Field name {} is reserved, names must not start with `__`",
+                field.name()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_key(config: &Config) -> ConfigResult<()> {
+    match config.key_field().map(FieldConfig::data_type) {
+        Some(DataType::Int { .. }) => Ok(()),
+        Some(_) => Err(ConfigError::format(format_args!(
+            "Key column {} must have Int data type",
+            config.datasource().key()
+        ))),
+        None => Err(ConfigError::format(format_args!(
+            "Key column {} is not defined in schema",
+            config.datasource().key()
+        ))),
+    }
+}
+
 fn validate_dir<P>(path: P, name: &str) -> ConfigResult<()>
 where
     P: AsRef<Path>,
@@ -0,0 +1,735 @@
+use crate::config::ConfigRef;
+use crate::config::DataType;
+use crate::config::FieldConfig;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::index::PRESENT_FIELD;
+use crate::parser::Occurance;
+use crate::parser::RangeBound;
+use crate::parser::Token;
+use crate::query::BoostQuery;
+use levenshtein_automata::LevenshteinAutomatonBuilder;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Bound;
+use std::str::FromStr;
+use tantivy::query::AllQuery;
+use tantivy::query::BooleanQuery;
+use tantivy::query::Occur;
+use tantivy::query::PhraseQuery;
+use tantivy::query::Query;
+use tantivy::query::RangeQuery;
+use tantivy::query::RegexQuery;
+use tantivy::query::TermQuery;
+use tantivy::schema::Field;
+use tantivy::schema::IndexRecordOption;
+use tantivy::Searcher;
+use tantivy::Term;
+use tantivy_fst::Automaton;
+use tantivy_fst::Regex;
+
+const MAX_PHRASE_VARIANTS: usize = 1024;
+
+// Compiles parsed tokens to tantivy query, shared by all handlers which search index.
+#[derive(Debug, Clone)]
+pub struct QueryBuilder {
+    config: ConfigRef,
+}
+
+impl QueryBuilder {
+    pub fn new(config: &ConfigRef) -> QueryBuilder {
+        QueryBuilder {
+            config: config.clone(),
+        }
+    }
+
+    pub fn build_query(
+        &self,
+        tokens: &[Token],
+        field_schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+    ) -> HandlerResult<Box<Query>> {
+        let mut fields = IndexFields {
+            text_fields: HashMap::new(),
+            u64_fields: HashMap::new(),
+            i64_fields: HashMap::new(),
+            present_field: field_schema.get(PRESENT_FIELD).cloned(),
+        };
+
+        for field_config in self.config.schema() {
+            let name = field_config.name();
+            let field = match field_schema.get(name) {
+                Some(&field) => field,
+                None => continue,
+            };
+
+            match self.field_kind(name)? {
+                (FieldKind::Text, true) => fields.text_fields.insert(name, field),
+                (FieldKind::U64, true) => fields.u64_fields.insert(name, field),
+                (FieldKind::I64, true) => fields.i64_fields.insert(name, field),
+                _ => None,
+            };
+        }
+
+        self.build_tokens_query(tokens, &fields, searcher)
+    }
+
+    // Returns type of configured field and whether it is indexed, key field is always indexed.
+    pub fn field_kind(&self, name: &str) -> HandlerResult<(FieldKind, bool)> {
+        let field_config = self
+            .config
+            .schema()
+            .iter()
+            .find(|field| field.name() == name)
+            .ok_or_else(|| HandlerError::new(&format!("Field `{}` not defined", name)))?;
+        let is_key = self.config.key_field().map(FieldConfig::name) == Some(name);
+
+        Ok(match field_config.data_type() {
+            DataType::Int { indexed, .. } => (FieldKind::I64, indexed || is_key),
+            DataType::UInt { indexed, .. } => (FieldKind::U64, indexed),
+            DataType::Text => (FieldKind::Text, true),
+        })
+    }
+
+    // Checks fields and values of single term without reading index, so query can be validated before search.
+    pub fn check_token(&self, token: &Token) -> HandlerResult<()> {
+        match token {
+            Token::FilterEquals { field, value, .. } => self.check_value(field, value),
+            Token::FilterIn { field, values, .. } => {
+                for value in values {
+                    self.check_value(field, value)?;
+                }
+
+                Ok(())
+            }
+            Token::FilterExists { field, .. } => self.field_kind(field).map(|_| ()),
+            Token::FilterRange {
+                field,
+                left_bound,
+                right_bound,
+                ..
+            } => match self.resolve_field(field)? {
+                FieldKind::I64 => {
+                    self.parse_bound::<i64, _>(left_bound)?;
+                    self.parse_bound::<i64, _>(right_bound).map(|_| ())
+                }
+                FieldKind::U64 => {
+                    self.parse_bound::<u64, _>(left_bound)?;
+                    self.parse_bound::<u64, _>(right_bound).map(|_| ())
+                }
+                FieldKind::Text => {
+                    Err(HandlerError::new(&format!("Field `{}` not numeric", field)))
+                }
+            },
+            Token::Phrase {
+                field,
+                strings,
+                slop,
+                ..
+            } => {
+                self.check_text_field(field)?;
+
+                if strings.is_empty() {
+                    return Err(HandlerError::new("Phrase does not contain any words"));
+                }
+
+                if strings.len() > 1 && *slop > 0 {
+                    phrase_offsets(strings.len(), *slop)?;
+                }
+
+                Ok(())
+            }
+            Token::Wildcard { field, .. }
+            | Token::Fuzzy { field, .. }
+            | Token::Regex { field, .. } => self.check_text_field(field),
+            Token::Text { .. } | Token::Group { .. } | Token::Boost { .. } => Ok(()),
+        }
+    }
+
+    fn build_tokens_query(
+        &self,
+        tokens: &[Token],
+        fields: &IndexFields,
+        searcher: &Searcher,
+    ) -> HandlerResult<Box<Query>> {
+        let mut terms = Vec::new();
+
+        for token in tokens {
+            self.build_token_query(token, fields, searcher, &mut terms)?;
+        }
+
+        Ok(Box::new(BooleanQuery::from(terms)))
+    }
+
+    fn build_token_query(
+        &self,
+        token: &Token,
+        fields: &IndexFields,
+        searcher: &Searcher,
+        terms: &mut Vec<(Occur, Box<Query>)>,
+    ) -> HandlerResult<()> {
+        self.check_token(token)?;
+
+        match token {
+            Token::Text { occurance, strings } => {
+                let occur = self.map_occurance(occurance);
+
+                for string in strings {
+                    let string = string.to_lowercase();
+                    let inner_terms: Vec<_> = fields
+                        .text_fields
+                        .values()
+                        .cloned()
+                        .map(|field| (Occur::Should, self.create_term_query_text(field, &string)))
+                        .collect();
+
+                    terms.push((occur, Box::new(BooleanQuery::from(inner_terms))));
+                }
+            }
+            Token::FilterEquals {
+                occurance,
+                field,
+                value,
+            } => {
+                let occur = self.map_occurance(occurance);
+
+                terms.push((occur, self.create_equals_query(fields, field, value)?));
+            }
+            Token::FilterExists {
+                occurance,
+                field,
+                exists,
+            } => {
+                let occur = self.map_occurance(occurance);
+
+                terms.push((occur, self.create_exists_query(fields, field, *exists)?));
+            }
+            Token::FilterIn {
+                occurance,
+                field,
+                values,
+            } => {
+                let occur = self.map_occurance(occurance);
+                let mut inner_terms = Vec::new();
+
+                for value in values {
+                    inner_terms.push((
+                        Occur::Should,
+                        self.create_equals_query(fields, field, value)?,
+                    ));
+                }
+
+                terms.push((occur, Box::new(BooleanQuery::from(inner_terms))));
+            }
+            Token::FilterRange {
+                occurance,
+                field,
+                left_bound,
+                right_bound,
+            } => {
+                let occur = self.map_occurance(occurance);
+
+                if let Some(field) = fields.u64_fields.get(field.as_str()).cloned() {
+                    let left_bound = self.parse_bound(left_bound)?;
+                    let right_bound = self.parse_bound(right_bound)?;
+
+                    terms.push((
+                        occur,
+                        self.create_bound_query_u64(field, left_bound, right_bound),
+                    ));
+                } else if let Some(field) = fields.i64_fields.get(field.as_str()).cloned() {
+                    let left_bound = self.parse_bound(left_bound)?;
+                    let right_bound = self.parse_bound(right_bound)?;
+
+                    terms.push((
+                        occur,
+                        self.create_bound_query_i64(field, left_bound, right_bound),
+                    ));
+                } else {
+                    return Err(HandlerError::new(&format!("Field `{}` not numeric", field)));
+                }
+            }
+            Token::Phrase {
+                occurance,
+                field,
+                strings,
+                slop,
+            } => {
+                let occur = self.map_occurance(occurance);
+                let strings: Vec<_> = strings.iter().map(|s| s.to_lowercase()).collect();
+                let mut inner_terms = Vec::new();
+
+                for field in self.text_fields(fields, field)? {
+                    inner_terms.push((
+                        Occur::Should,
+                        self.create_phrase_query(field, &strings, *slop)?,
+                    ));
+                }
+
+                terms.push((occur, Box::new(BooleanQuery::from(inner_terms))));
+            }
+            Token::Wildcard {
+                occurance,
+                field,
+                pattern,
+            } => {
+                let occur = self.map_occurance(occurance);
+                let pattern = pattern.to_lowercase();
+                let regex = Regex::new(&wildcard_to_regex(&pattern)).map_err(|err| {
+                    HandlerError::new(&format!("Invalid wildcard `{}` - {}", pattern, err))
+                })?;
+                let mut inner_terms = Vec::new();
+
+                for field in self.text_fields(fields, field)? {
+                    inner_terms.push((
+                        Occur::Should,
+                        self.create_automaton_query(searcher, field, &regex, &pattern)?,
+                    ));
+                }
+
+                terms.push((occur, Box::new(BooleanQuery::from(inner_terms))));
+            }
+            Token::Fuzzy {
+                occurance,
+                field,
+                string,
+                distance,
+            } => {
+                let occur = self.map_occurance(occurance);
+                let string = string.to_lowercase();
+                let automaton =
+                    LevenshteinAutomatonBuilder::new(*distance, true).build_dfa(&string);
+                let mut inner_terms = Vec::new();
+
+                for field in self.text_fields(fields, field)? {
+                    inner_terms.push((
+                        Occur::Should,
+                        self.create_frequent_terms_query(searcher, field, &automaton),
+                    ));
+                }
+
+                terms.push((occur, Box::new(BooleanQuery::from(inner_terms))));
+            }
+            Token::Regex {
+                occurance,
+                field,
+                pattern,
+            } => {
+                let occur = self.map_occurance(occurance);
+                let mut inner_terms: Vec<(_, Box<Query>)> = Vec::new();
+
+                for field in self.text_fields(fields, field)? {
+                    inner_terms.push((
+                        Occur::Should,
+                        Box::new(RegexQuery::new(pattern.clone(), field)),
+                    ));
+                }
+
+                terms.push((occur, Box::new(BooleanQuery::from(inner_terms))));
+            }
+            Token::Group { occurance, tokens } => {
+                let occur = self.map_occurance(occurance);
+
+                terms.push((occur, self.build_tokens_query(tokens, fields, searcher)?));
+            }
+            Token::Boost { token, boost } => {
+                let boost = boost
+                    .parse()
+                    .map_err(|_| HandlerError::new(&format!("Boost `{}` not a number", boost)))?;
+                let mut inner_terms = Vec::new();
+
+                self.build_token_query(token, fields, searcher, &mut inner_terms)?;
+
+                for (occur, query) in inner_terms {
+                    terms.push((occur, Box::new(BoostQuery::new(query, boost))));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn resolve_field(&self, name: &str) -> HandlerResult<FieldKind> {
+        match self.field_kind(name)? {
+            (kind, true) => Ok(kind),
+            (_, false) => Err(HandlerError::new(&format!("Field `{}` not indexed", name))),
+        }
+    }
+
+    fn check_value(&self, field: &str, value: &str) -> HandlerResult<()> {
+        match self.resolve_field(field)? {
+            FieldKind::Text if words(value).is_empty() => Err(HandlerError::new(&format!(
+                "Value `{}` does not contain any words",
+                value
+            ))),
+            FieldKind::Text => Ok(()),
+            FieldKind::I64 => self.parse_value::<i64, _>(value).map(|_| ()),
+            FieldKind::U64 => self.parse_value::<u64, _>(value).map(|_| ()),
+        }
+    }
+
+    fn check_text_field(&self, field: &Option<String>) -> HandlerResult<()> {
+        match field {
+            Some(field) if self.resolve_field(field)? != FieldKind::Text => {
+                Err(HandlerError::new(&format!("Field `{}` not text", field)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn create_equals_query(
+        &self,
+        fields: &IndexFields,
+        field: &str,
+        value: &str,
+    ) -> HandlerResult<Box<Query>> {
+        if let Some(field) = fields.text_fields.get(field).cloned() {
+            self.create_phrase_query(field, &words(value), 0)
+        } else if let Some(field) = fields.u64_fields.get(field).cloned() {
+            Ok(self.create_term_query_u64(field, self.parse_value(value)?))
+        } else if let Some(field) = fields.i64_fields.get(field).cloned() {
+            Ok(self.create_term_query_i64(field, self.parse_value(value)?))
+        } else {
+            Err(HandlerError::new(&format!("Field `{}` not defined", field)))
+        }
+    }
+
+    fn create_exists_query(
+        &self,
+        fields: &IndexFields,
+        field: &str,
+        exists: bool,
+    ) -> HandlerResult<Box<Query>> {
+        let present_field = fields
+            .present_field
+            .ok_or_else(|| HandlerError::new("Index does not contain present fields"))?;
+        let query: Box<Query> = Box::new(TermQuery::new(
+            Term::from_field_text(present_field, field),
+            IndexRecordOption::Basic,
+        ));
+
+        if exists {
+            Ok(query)
+        } else {
+            Ok(Box::new(BooleanQuery::from(vec![
+                (Occur::Must, Box::new(AllQuery) as Box<Query>),
+                (Occur::MustNot, query),
+            ])))
+        }
+    }
+
+    fn text_fields(
+        &self,
+        fields: &IndexFields,
+        field: &Option<String>,
+    ) -> HandlerResult<Vec<Field>> {
+        match field {
+            Some(field) => match fields.text_fields.get(field.as_str()) {
+                Some(&field) => Ok(vec![field]),
+                None => Err(HandlerError::new(&format!("Field `{}` not text", field))),
+            },
+            None => Ok(fields.text_fields.values().cloned().collect()),
+        }
+    }
+
+    fn map_occurance(&self, occurance: &Option<Occurance>) -> Occur {
+        match occurance {
+            Some(Occurance::Must) => Occur::Must,
+            Some(Occurance::MustNot) => Occur::MustNot,
+            None => Occur::Should,
+        }
+    }
+
+    fn parse_bound<T, E>(&self, value: &RangeBound) -> HandlerResult<Bound<T>>
+    where
+        T: FromStr<Err = E>,
+        E: Display,
+    {
+        match value {
+            RangeBound::Included(value) if value.is_empty() => Ok(Bound::Unbounded),
+            RangeBound::Excluded(value) if value.is_empty() => Ok(Bound::Unbounded),
+            RangeBound::Included(value) => Ok(Bound::Included(self.parse_value(value)?)),
+            RangeBound::Excluded(value) => Ok(Bound::Excluded(self.parse_value(value)?)),
+            RangeBound::Unbounded => Ok(Bound::Unbounded),
+        }
+    }
+
+    fn parse_value<T, E>(&self, value: &str) -> HandlerResult<T>
+    where
+        T: FromStr<Err = E>,
+        E: Display,
+    {
+        value.parse().map_err(|err| {
+            HandlerError::new(&format!("Failed to parse value `{}` - {}", value, err))
+        })
+    }
+
+    fn create_bound_query_i64(
+        &self,
+        field: Field,
+        left_bound: Bound<i64>,
+        right_bound: Bound<i64>,
+    ) -> Box<Query> {
+        Box::new(RangeQuery::new_i64_bounds(field, left_bound, right_bound))
+    }
+
+    fn create_bound_query_u64(
+        &self,
+        field: Field,
+        left_bound: Bound<u64>,
+        right_bound: Bound<u64>,
+    ) -> Box<Query> {
+        Box::new(RangeQuery::new_u64_bounds(field, left_bound, right_bound))
+    }
+
+    fn create_term_query_i64(&self, field: Field, value: i64) -> Box<Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_i64(field, value),
+            IndexRecordOption::WithFreqs,
+        ))
+    }
+
+    fn create_term_query_u64(&self, field: Field, value: u64) -> Box<Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_u64(field, value),
+            IndexRecordOption::WithFreqs,
+        ))
+    }
+
+    fn create_term_query_text(&self, field: Field, value: &str) -> Box<Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(field, value),
+            IndexRecordOption::WithFreqs,
+        ))
+    }
+
+    // Tantivy fuzzy query does not count transposition as one edit, so terms are expanded here.
+    // Short fuzzy terms match many index terms, only the most frequent of them are searched.
+    fn create_frequent_terms_query<A>(
+        &self,
+        searcher: &Searcher,
+        field: Field,
+        automaton: &A,
+    ) -> Box<Query>
+    where
+        A: Automaton,
+    {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field);
+            let mut stream = inverted_index.terms().search(automaton).into_stream();
+
+            while stream.advance() {
+                let string = String::from_utf8_lossy(stream.key()).into_owned();
+
+                *counts.entry(string).or_insert(0) += u64::from(stream.value().doc_freq);
+            }
+        }
+
+        let mut strings: Vec<_> = counts.into_iter().collect();
+
+        strings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        strings.truncate(self.config.search().max_expansions());
+
+        let terms: Vec<_> = strings
+            .iter()
+            .map(|(string, _)| (Occur::Should, self.create_term_query_text(field, string)))
+            .collect();
+
+        Box::new(BooleanQuery::from(terms))
+    }
+
+    fn create_automaton_query<A>(
+        &self,
+        searcher: &Searcher,
+        field: Field,
+        automaton: &A,
+        value: &str,
+    ) -> HandlerResult<Box<Query>>
+    where
+        A: Automaton,
+    {
+        let max_expansions = self.config.search().max_expansions();
+        let mut strings = BTreeSet::new();
+
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field);
+            let mut stream = inverted_index.terms().search(automaton).into_stream();
+
+            while stream.advance() {
+                strings.insert(String::from_utf8_lossy(stream.key()).into_owned());
+
+                if strings.len() > max_expansions {
+                    return Err(HandlerError::new(&format!(
+                        "Term `{}` matches more than {} index terms",
+                        value, max_expansions
+                    )));
+                }
+            }
+        }
+
+        let terms: Vec<_> = strings
+            .iter()
+            .map(|string| (Occur::Should, self.create_term_query_text(field, string)))
+            .collect();
+
+        Ok(Box::new(BooleanQuery::from(terms)))
+    }
+
+    fn create_phrase_query(
+        &self,
+        field: Field,
+        strings: &[String],
+        slop: u32,
+    ) -> HandlerResult<Box<Query>> {
+        match strings.len() {
+            0 => return Err(HandlerError::new("Phrase does not contain any words")),
+            1 => return Ok(self.create_term_query_text(field, &strings[0])),
+            _ => {}
+        }
+
+        let terms: Vec<_> = strings
+            .iter()
+            .map(|string| Term::from_field_text(field, string))
+            .collect();
+
+        if slop == 0 {
+            return Ok(Box::new(PhraseQuery::new(terms)));
+        }
+
+        // Tantivy phrase queries have no slop, so every in-order placement of the
+        // terms with at most `slop` extra positions becomes a separate phrase.
+        let mut variants: Vec<(_, Box<Query>)> = Vec::new();
+
+        for offsets in phrase_offsets(terms.len(), slop)? {
+            let terms = offsets.into_iter().zip(terms.iter().cloned()).collect();
+
+            variants.push((Occur::Should, Box::new(PhraseQuery::new_with_offset(terms))));
+        }
+
+        Ok(Box::new(BooleanQuery::from(variants)))
+    }
+}
+
+// Variant count is checked on every push, so large slop is rejected before offsets are allocated.
+fn phrase_offsets(length: usize, slop: u32) -> HandlerResult<Vec<Vec<usize>>> {
+    let mut result = vec![vec![0]];
+
+    for _ in 1..length {
+        let mut next = Vec::new();
+
+        for offsets in result {
+            let last = offsets[offsets.len() - 1];
+            let used = last + 1 - offsets.len();
+
+            for gap in 0..=(slop as usize - used) {
+                if next.len() == MAX_PHRASE_VARIANTS {
+                    return Err(HandlerError::new(&format!(
+                        "Phrase slop {} is too large for {} words",
+                        slop, length
+                    )));
+                }
+
+                let mut offsets = offsets.clone();
+                offsets.push(last + 1 + gap);
+                next.push(offsets);
+            }
+        }
+
+        result = next;
+    }
+
+    Ok(result)
+}
+
+// Text filter values are matched as phrase of their words.
+fn words(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn wildcard_to_regex(pattern: &str) -> String {
+    pattern
+        .chars()
+        .map(|c| match c {
+            '*' => ".*".into(),
+            '?' => ".".into(),
+            c if "\\.+()|[]{}^$#&-~".contains(c) => format!("\\{}", c),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+// Type of searchable field, index schema is created from configuration by the same rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Text,
+    I64,
+    U64,
+}
+
+impl FieldKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FieldKind::Text => "text",
+            FieldKind::I64 => "int",
+            FieldKind::U64 => "uint",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct IndexFields<'a> {
+    text_fields: HashMap<&'a str, Field>,
+    u64_fields: HashMap<&'a str, Field>,
+    i64_fields: HashMap<&'a str, Field>,
+    present_field: Option<Field>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_phrase_offsets_with_slop() {
+        let offsets = phrase_offsets(2, 1);
+
+        assert_eq!(offsets.is_ok(), true);
+        assert_eq!(offsets.unwrap(), vec![vec![0, 1], vec![0, 2]]);
+    }
+
+    #[test]
+    fn should_build_phrase_offsets_without_slop() {
+        let offsets = phrase_offsets(3, 0);
+
+        assert_eq!(offsets.is_ok(), true);
+        assert_eq!(offsets.unwrap(), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn should_build_phrase_offsets_of_max_variants() {
+        let offsets = phrase_offsets(2, MAX_PHRASE_VARIANTS as u32 - 1);
+
+        assert_eq!(offsets.is_ok(), true);
+        assert_eq!(offsets.unwrap().len(), MAX_PHRASE_VARIANTS);
+    }
+
+    #[test]
+    fn should_fail_phrase_offsets_with_large_slop() {
+        let offsets = phrase_offsets(2, 5_000_000);
+
+        assert_eq!(offsets.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_phrase_offsets_with_max_slop() {
+        let offsets = phrase_offsets(3, u32::MAX);
+
+        assert_eq!(offsets.is_err(), true);
+    }
+}
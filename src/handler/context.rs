@@ -0,0 +1,231 @@
+use crate::collector::FastField;
+use crate::collector::SortCollector;
+use crate::collector::SortOrder;
+use crate::config::ConfigRef;
+use crate::handler::builder::QueryBuilder;
+use crate::handler::query::RequestQuery;
+use crate::handler::util::document_to_map;
+use crate::handler::util::filter_tokens;
+use crate::handler::util::find_document;
+use crate::handler::util::handle_request;
+use crate::handler::util::key_field;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::index::TextIndexRef;
+use crate::parser::Occurance;
+use crate::parser::RangeBound;
+use crate::parser::Token;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::collections::HashMap;
+use tantivy::schema::Field;
+use tantivy::IndexReader;
+use tantivy::Searcher;
+
+#[derive(Debug)]
+pub struct ContextHandler {
+    config: ConfigRef,
+    text_index: TextIndexRef,
+    query_builder: QueryBuilder,
+}
+
+impl ContextHandler {
+    pub fn new(config: &ConfigRef, text_index: &TextIndexRef) -> ContextHandler {
+        ContextHandler {
+            config: config.clone(),
+            text_index: text_index.clone(),
+            query_builder: QueryBuilder::new(config),
+        }
+    }
+
+    fn process_query(
+        &self,
+        reader: &IndexReader,
+        schema: &HashMap<String, Field>,
+        filter: &[Token],
+        key: i64,
+        size: usize,
+    ) -> HandlerResult<Response> {
+        let searcher = reader.searcher();
+        let key_field = key_field(&self.config, schema)?;
+        let address = find_document(&searcher, key_field, key)?
+            .ok_or_else(|| HandlerError::new(&format!("Document `{}` not found", key)))?;
+        let retrieved_doc = searcher
+            .doc(address)
+            .map_err(|err| HandlerError::new(&format!("Search document - {:?}", err)))?;
+        let before_range = (RangeBound::Unbounded, RangeBound::Excluded(key.to_string()));
+        let after_range = (RangeBound::Excluded(key.to_string()), RangeBound::Unbounded);
+        let mut before = self.neighbours(
+            schema,
+            &searcher,
+            filter,
+            before_range,
+            SortOrder::Desc,
+            size,
+        )?;
+
+        // Previous documents are searched from the nearest one, but returned in key order.
+        before.reverse();
+
+        Ok(Response {
+            key,
+            before,
+            document: document_to_map(searcher.schema(), &retrieved_doc),
+            after: self.neighbours(schema, &searcher, filter, after_range, SortOrder::Asc, size)?,
+        })
+    }
+
+    fn neighbours(
+        &self,
+        schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+        filter: &[Token],
+        (left_bound, right_bound): (RangeBound, RangeBound),
+        order: SortOrder,
+        size: usize,
+    ) -> HandlerResult<Vec<HashMap<String, Option<String>>>> {
+        let key_field = key_field(&self.config, schema)?;
+        let key_name = searcher.schema().get_field_name(key_field).to_string();
+        let mut tokens = filter_tokens(filter);
+
+        tokens.push(Token::FilterRange {
+            occurance: Some(Occurance::Must),
+            field: key_name,
+            left_bound,
+            right_bound,
+        });
+
+        let query = self.query_builder.build_query(&tokens, schema, searcher)?;
+        let collector =
+            SortCollector::new(vec![(FastField::I64(key_field), order)], key_field, size);
+        let sorted_docs = searcher
+            .search(&query, &collector)
+            .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+        let mut result = Vec::new();
+
+        for sorted_doc in &sorted_docs {
+            let retrieved_doc = searcher
+                .doc(sorted_doc.address())
+                .map_err(|err| HandlerError::new(&format!("Search document - {:?}", err)))?;
+
+            result.push(document_to_map(searcher.schema(), &retrieved_doc));
+        }
+
+        Ok(result)
+    }
+}
+
+impl Handler for ContextHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        handle_request(request, move |request: Request| {
+            let max_limit = self.config.search().max_limit();
+            let filter = match &request.query {
+                Some(query) => query.to_tokens()?,
+                None => Vec::new(),
+            };
+
+            if request.size == 0 || request.size > max_limit {
+                return Err(HandlerError::new(&format!(
+                    "Size must be from 1 to {}, but {} given",
+                    max_limit, request.size
+                )));
+            }
+
+            self.text_index
+                .read(|reader, schema| {
+                    self.process_query(reader, schema, &filter, request.key, request.size)
+                })
+                .map_err(|err| HandlerError::new(&format!("{}", err)))?
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    key: i64,
+    size: usize,
+    query: Option<RequestQuery>,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    key: i64,
+    before: Vec<HashMap<String, Option<String>>>,
+    document: HashMap<String, Option<String>>,
+    after: Vec<HashMap<String, Option<String>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+    use crate::parser::parse_query;
+
+    fn context(key: i64, size: usize, filter: &str) -> HandlerResult<(Vec<String>, Vec<String>)> {
+        let index = create_index(&[
+            &[("id", "-3"), ("level", "error")],
+            &[("id", "1"), ("level", "info")],
+            &[("id", "2"), ("level", "error")],
+            &[("id", "5"), ("level", "info")],
+            &[("id", "8"), ("level", "error")],
+        ]);
+        let handler = ContextHandler::new(&index.config, &index.text_index);
+        let filter = if filter.is_empty() {
+            Vec::new()
+        } else {
+            parse_query(filter).unwrap()
+        };
+        let keys = |docs: &[HashMap<String, Option<String>>]| {
+            docs.iter()
+                .map(|doc| doc["id"].clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        index
+            .text_index
+            .read(|reader, schema| handler.process_query(reader, schema, &filter, key, size))
+            .unwrap()
+            .map(|response| (keys(&response.before), keys(&response.after)))
+    }
+
+    #[test]
+    fn should_find_documents_around_key() {
+        let (before, after) = context(2, 2, "").unwrap();
+
+        assert_eq!(before, vec!["-3", "1"]);
+        assert_eq!(after, vec!["5", "8"]);
+    }
+
+    #[test]
+    fn should_find_documents_around_first_and_last_key() {
+        let (before, after) = context(-3, 2, "").unwrap();
+
+        assert_eq!(before.is_empty(), true);
+        assert_eq!(after, vec!["1", "2"]);
+
+        let (before, after) = context(8, 10, "").unwrap();
+
+        assert_eq!(before, vec!["-3", "1", "2", "5"]);
+        assert_eq!(after.is_empty(), true);
+    }
+
+    #[test]
+    fn should_find_filtered_documents_around_key() {
+        let (before, after) = context(2, 1, "level:error").unwrap();
+
+        assert_eq!(before, vec!["-3"]);
+        assert_eq!(after, vec!["8"]);
+
+        let (before, after) = context(2, 5, "-level:error").unwrap();
+
+        assert_eq!(before, vec!["1"]);
+        assert_eq!(after, vec!["5"]);
+    }
+
+    #[test]
+    fn should_fail_when_document_not_found() {
+        assert_eq!(context(3, 1, "").is_err(), true);
+    }
+}
@@ -0,0 +1,328 @@
+use crate::collector::SortPosition;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+use std::time::Instant;
+use tantivy::IndexReader;
+use tantivy::ReloadPolicy;
+use tantivy::Searcher;
+
+const MAX_PINNED_READERS: usize = 16;
+
+// Position of the last returned document in pinned index generation.
+#[derive(Debug)]
+pub struct Cursor {
+    generation: u64,
+    request: u64,
+    position: SortPosition,
+}
+
+impl Cursor {
+    pub fn new<R>(generation: u64, request: &R, position: SortPosition) -> Cursor
+    where
+        R: Debug,
+    {
+        Cursor {
+            generation,
+            request: hash(&format!("{:?}", request)),
+            position,
+        }
+    }
+
+    pub fn decode(value: &str) -> HandlerResult<Cursor> {
+        let invalid = || HandlerError::new(&format!("Invalid cursor `{}`", value));
+        let parts: Vec<_> = value.split('.').collect();
+
+        if parts.len() != 5 {
+            return Err(invalid());
+        }
+
+        let values = if parts[4].is_empty() {
+            Vec::new()
+        } else {
+            parts[4]
+                .split(',')
+                .map(|value| value.parse().map_err(|_| invalid()))
+                .collect::<HandlerResult<_>>()?
+        };
+
+        Ok(Cursor {
+            generation: u64::from_str_radix(parts[0], 16).map_err(|_| invalid())?,
+            request: u64::from_str_radix(parts[1], 16).map_err(|_| invalid())?,
+            position: SortPosition::new(
+                values,
+                f32::from_bits(u32::from_str_radix(parts[2], 16).map_err(|_| invalid())?),
+                parts[3].parse().map_err(|_| invalid())?,
+            ),
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let values: Vec<_> = self
+            .position
+            .values()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        format!(
+            "{:x}.{:x}.{:x}.{}.{}",
+            self.generation,
+            self.request,
+            self.position.score().to_bits(),
+            self.position.key(),
+            values.join(",")
+        )
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Cursor can be used only with the same query and sort order.
+    pub fn check_request<R>(&self, request: &R) -> HandlerResult<()>
+    where
+        R: Debug,
+    {
+        if self.request == hash(&format!("{:?}", request)) {
+            Ok(())
+        } else {
+            Err(HandlerError::new(
+                "Cursor was created for another query or sort order",
+            ))
+        }
+    }
+
+    pub fn position(&self) -> &SortPosition {
+        &self.position
+    }
+}
+
+// Readers which are not reloaded on commit, so pages of one cursor are read from the same segments.
+pub struct PinnedReaders {
+    readers: Mutex<HashMap<u64, (Instant, IndexReader)>>,
+    timeout: Duration,
+}
+
+impl PinnedReaders {
+    pub fn new(timeout: u64) -> PinnedReaders {
+        PinnedReaders {
+            readers: Mutex::new(HashMap::new()),
+            timeout: Duration::from_secs(timeout),
+        }
+    }
+
+    // Returns reader of cursor generation, which was pinned when the cursor was created.
+    pub fn get(&self, cursor: &Cursor) -> HandlerResult<IndexReader> {
+        let mut readers = self.lock()?;
+
+        match readers.get_mut(&cursor.generation()) {
+            Some((used, reader)) => {
+                *used = Instant::now();
+
+                Ok(reader.clone())
+            }
+            None => Err(HandlerError::new(
+                "Cursor is expired, search must be started from the first page",
+            )),
+        }
+    }
+
+    // Pins generation of searcher, which is called only when the next cursor is returned.
+    pub fn pin(&self, searcher: &Searcher) -> HandlerResult<u64> {
+        let mut readers = self.lock()?;
+        let now = Instant::now();
+        let generation = generation(searcher);
+
+        if let Some((used, _)) = readers.get_mut(&generation) {
+            *used = now;
+
+            return Ok(generation);
+        }
+
+        // Index can be committed after the first page, then the next pages are read from newer segments.
+        let pinned_reader: IndexReader = searcher
+            .index()
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .num_searchers(1)
+            .try_into()
+            .map_err(|err| HandlerError::new(&format!("Failed to pin reader - {:?}", err)))?;
+        let generation = self::generation(&pinned_reader.searcher());
+
+        // Every pinned reader keeps files of its segments open, so the oldest one is released first.
+        while readers.len() >= MAX_PINNED_READERS {
+            let oldest = readers
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(&generation, _)| generation);
+
+            match oldest {
+                Some(oldest) => readers.remove(&oldest),
+                None => break,
+            };
+        }
+
+        readers.insert(generation, (now, pinned_reader));
+
+        Ok(generation)
+    }
+
+    #[cfg(test)]
+    pub fn count(&self) -> usize {
+        self.lock().unwrap().len()
+    }
+
+    // Expired readers are released on every access.
+    fn lock(&self) -> HandlerResult<MutexGuard<'_, HashMap<u64, (Instant, IndexReader)>>> {
+        let mut readers = self
+            .readers
+            .lock()
+            .map_err(|_| HandlerError::new("Pinned readers lock is poisoned"))?;
+        let now = Instant::now();
+        let timeout = self.timeout;
+
+        readers.retain(|_, (used, _)| now.duration_since(*used) < timeout);
+
+        Ok(readers)
+    }
+}
+
+impl Debug for PinnedReaders {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "PinnedReaders {{ timeout: {:?}, ... }}", self.timeout)
+    }
+}
+
+// Generation changes when segments are added, merged or contain new deletes.
+fn generation(searcher: &Searcher) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for segment_reader in searcher.segment_readers() {
+        segment_reader.segment_id().hash(&mut hasher);
+        segment_reader.num_deleted_docs().hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+fn hash(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+
+    #[test]
+    fn should_encode_and_decode_cursor() {
+        let position = SortPosition::new(vec![-5, 12, -170_141_183_460_469_231_731], -1.5, -42);
+        let cursor = Cursor::new(7, &"query", position);
+        let decoded = Cursor::decode(&cursor.encode());
+
+        assert_eq!(decoded.is_ok(), true);
+
+        let decoded = decoded.unwrap();
+
+        assert_eq!(decoded.generation(), 7);
+        assert_eq!(decoded.check_request(&"query").is_ok(), true);
+        assert_eq!(decoded.check_request(&"other").is_err(), true);
+        assert_eq!(
+            decoded.position().values(),
+            &[-5, 12, -170_141_183_460_469_231_731]
+        );
+        assert_eq!(decoded.position().score(), -1.5);
+        assert_eq!(decoded.position().key(), -42);
+    }
+
+    #[test]
+    fn should_encode_and_decode_cursor_without_values() {
+        let cursor = Cursor::new(0, &"query", SortPosition::new(Vec::new(), 0.0, 1));
+        let decoded = Cursor::decode(&cursor.encode());
+
+        assert_eq!(decoded.is_ok(), true);
+
+        let decoded = decoded.unwrap();
+
+        assert_eq!(decoded.position().values().is_empty(), true);
+        assert_eq!(decoded.position().score(), 0.0);
+        assert_eq!(decoded.position().key(), 1);
+    }
+
+    #[test]
+    fn should_fail_when_cursor_malformed() {
+        let values = [
+            "",
+            "1.2.0.3",
+            "1.2.0.3.4.5",
+            "x.2.0.3.4",
+            "1.x.0.3.4",
+            "1.2.x.3.4",
+            "1.2.0.x.4",
+            "1.2.0.3.x",
+            "1.2.0.3.4,",
+            "1.2.0..4",
+            "1.2.100000000.3.4",
+        ];
+
+        for value in values.iter() {
+            assert_eq!(Cursor::decode(value).is_err(), true, "{}", value);
+        }
+    }
+
+    #[test]
+    fn should_release_oldest_pinned_reader() {
+        let mut index = create_index(&[&[("id", "0")]]);
+        let pinned_readers = PinnedReaders::new(600);
+        let mut generations = Vec::new();
+
+        for key in 1..=MAX_PINNED_READERS {
+            let searcher = index
+                .text_index
+                .read(|reader, _| reader.searcher())
+                .unwrap();
+
+            generations.push(pinned_readers.pin(&searcher).unwrap());
+            index.add_documents(&[&[("id", &key.to_string())]]);
+        }
+
+        assert_eq!(pinned_readers.count(), MAX_PINNED_READERS);
+
+        let first = Cursor::new(
+            generations[0],
+            &"query",
+            SortPosition::new(Vec::new(), 0.0, 0),
+        );
+        let second = Cursor::new(
+            generations[1],
+            &"query",
+            SortPosition::new(Vec::new(), 0.0, 0),
+        );
+
+        assert_eq!(pinned_readers.get(&first).is_ok(), true);
+
+        let searcher = index
+            .text_index
+            .read(|reader, _| reader.searcher())
+            .unwrap();
+
+        pinned_readers.pin(&searcher).unwrap();
+
+        assert_eq!(pinned_readers.count(), MAX_PINNED_READERS);
+        assert_eq!(pinned_readers.get(&first).is_ok(), true);
+        assert_eq!(pinned_readers.get(&second).is_err(), true);
+    }
+}
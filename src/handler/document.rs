@@ -0,0 +1,66 @@
+use crate::config::ConfigRef;
+use crate::handler::util::document_to_map;
+use crate::handler::util::find_document;
+use crate::handler::util::handle_found;
+use crate::handler::util::key_field;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::index::TextIndexRef;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::collections::HashMap;
+use tantivy::schema::Field;
+use tantivy::IndexReader;
+
+#[derive(Debug)]
+pub struct DocumentHandler {
+    config: ConfigRef,
+    text_index: TextIndexRef,
+}
+
+impl DocumentHandler {
+    pub fn new(config: &ConfigRef, text_index: &TextIndexRef) -> DocumentHandler {
+        DocumentHandler {
+            config: config.clone(),
+            text_index: text_index.clone(),
+        }
+    }
+
+    fn process_query(
+        &self,
+        reader: &IndexReader,
+        schema: &HashMap<String, Field>,
+        key: i64,
+    ) -> HandlerResult<Option<HashMap<String, Option<String>>>> {
+        let searcher = reader.searcher();
+        let key_field = key_field(&self.config, schema)?;
+        let address = match find_document(&searcher, key_field, key)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+        let retrieved_doc = searcher
+            .doc(address)
+            .map_err(|err| HandlerError::new(&format!("Search document - {:?}", err)))?;
+
+        Ok(Some(document_to_map(searcher.schema(), &retrieved_doc)))
+    }
+}
+
+impl Handler for DocumentHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        // Handler is mounted on prefix, so path contains only the key.
+        let value = request.url.path().join("/");
+
+        handle_found(&format!("Document `{}` not found", value), move || {
+            let key = value
+                .parse()
+                .map_err(|_| HandlerError::new(&format!("Invalid key `{}`", value)))?;
+
+            self.text_index
+                .read(|reader, schema| self.process_query(reader, schema, key))
+                .map_err(|err| HandlerError::new(&format!("{}", err)))?
+        })
+    }
+}
@@ -0,0 +1,430 @@
+use crate::config::ConfigRef;
+use crate::handler::builder::QueryBuilder;
+use crate::handler::query::RequestQuery;
+use crate::handler::util::find_document;
+use crate::handler::util::handle_request;
+use crate::handler::util::key_field;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::index::TextIndexRef;
+use crate::parser::Occurance;
+use crate::parser::RangeBound;
+use crate::parser::Token;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::collections::HashMap;
+use tantivy::query::Query;
+use tantivy::schema::Field;
+use tantivy::DocAddress;
+use tantivy::DocSet;
+use tantivy::IndexReader;
+use tantivy::Score;
+use tantivy::Searcher;
+use tantivy::SkipResult;
+
+// Tantivy does not explain scores, so every clause of query is scored separately for the document.
+#[derive(Debug)]
+pub struct ExplainHandler {
+    config: ConfigRef,
+    text_index: TextIndexRef,
+    query_builder: QueryBuilder,
+}
+
+impl ExplainHandler {
+    pub fn new(config: &ConfigRef, text_index: &TextIndexRef) -> ExplainHandler {
+        ExplainHandler {
+            config: config.clone(),
+            text_index: text_index.clone(),
+            query_builder: QueryBuilder::new(config),
+        }
+    }
+
+    fn process_query(
+        &self,
+        reader: &IndexReader,
+        schema: &HashMap<String, Field>,
+        tokens: &[Token],
+        key: i64,
+    ) -> HandlerResult<Response> {
+        let searcher = reader.searcher();
+        let key_field = key_field(&self.config, schema)?;
+        let address = find_document(&searcher, key_field, key)?
+            .ok_or_else(|| HandlerError::new(&format!("Document `{}` not found", key)))?;
+        let query = self.query_builder.build_query(tokens, schema, &searcher)?;
+        let score = score_document(&*query, &searcher, address)?;
+        let clauses = self.explain_tokens(tokens, schema, &searcher, address)?;
+        let mut failures = Vec::new();
+
+        if score.is_none() {
+            collect_failures(&clauses, &mut failures);
+        }
+
+        Ok(Response {
+            key,
+            matched: score.is_some(),
+            score,
+            failures,
+            clauses,
+        })
+    }
+
+    fn explain_tokens(
+        &self,
+        tokens: &[Token],
+        schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+        address: DocAddress,
+    ) -> HandlerResult<Vec<Explanation>> {
+        tokens
+            .iter()
+            .map(|token| self.explain_token(token, schema, searcher, address))
+            .collect()
+    }
+
+    // Clause is scored without its occurance, so excluded clauses show whether the document contains them.
+    fn explain_token(
+        &self,
+        token: &Token,
+        schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+        address: DocAddress,
+    ) -> HandlerResult<Explanation> {
+        let mut clause = token.clone();
+        let occurance = clause.occurance_mut().take();
+        let query = self
+            .query_builder
+            .build_query(&[clause], schema, searcher)?;
+        let score = score_document(&*query, searcher, address)?;
+        let details = match unboosted(token) {
+            Token::Group { tokens, .. } => {
+                self.explain_tokens(tokens, schema, searcher, address)?
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(Explanation {
+            clause: describe(token),
+            occurance,
+            matched: score.is_some(),
+            score,
+            details,
+        })
+    }
+}
+
+impl Handler for ExplainHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        handle_request(request, move |request: Request| {
+            let tokens = request.query.to_tokens()?;
+
+            if tokens.is_empty() {
+                return Err(HandlerError::new("Query does not contain any terms"));
+            }
+
+            self.text_index
+                .read(|reader, schema| self.process_query(reader, schema, &tokens, request.key))
+                .map_err(|err| HandlerError::new(&format!("{}", err)))?
+        })
+    }
+}
+
+fn score_document(
+    query: &Query,
+    searcher: &Searcher,
+    address: DocAddress,
+) -> HandlerResult<Option<Score>> {
+    let DocAddress(segment_ord, doc) = address;
+    let weight = query
+        .weight(searcher, true)
+        .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+    let mut scorer = weight
+        .scorer(searcher.segment_reader(segment_ord))
+        .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+
+    if scorer.skip_next(doc) == SkipResult::Reached {
+        Ok(Some(scorer.score()))
+    } else {
+        Ok(None)
+    }
+}
+
+// Lists clauses which prevent the document from matching, required groups are explained by their own clauses.
+fn collect_failures(clauses: &[Explanation], failures: &mut Vec<String>) {
+    let mut has_required = false;
+    let mut optional = Vec::new();
+
+    for clause in clauses {
+        match (&clause.occurance, clause.matched) {
+            (Some(Occurance::Must), false) => {
+                failures.push(format!(
+                    "Required clause `{}` does not match",
+                    clause.clause
+                ));
+
+                // Only groups have details, other clauses are explained by themselves.
+                if !clause.details.is_empty() {
+                    collect_failures(&clause.details, failures);
+                }
+            }
+            (Some(Occurance::MustNot), true) => {
+                failures.push(format!("Excluded clause `{}` matches", clause.clause));
+            }
+            (None, matched) => optional.push((&clause.clause, matched)),
+            _ => {}
+        }
+
+        if let Some(Occurance::Must) = clause.occurance {
+            has_required = true;
+        }
+    }
+
+    if has_required {
+        return;
+    }
+
+    if optional.is_empty() {
+        failures.push("Query contains only excluded clauses".into());
+    } else if optional.iter().all(|&(_, matched)| !matched) {
+        let names: Vec<_> = optional
+            .iter()
+            .map(|(clause, _)| format!("`{}`", clause))
+            .collect();
+
+        failures.push(format!("None of clauses {} matches", names.join(", ")));
+    }
+}
+
+fn unboosted(token: &Token) -> &Token {
+    match token {
+        Token::Boost { token, .. } => unboosted(token),
+        token => token,
+    }
+}
+
+// Short query-like description of token, values are not escaped.
+fn describe(token: &Token) -> String {
+    let prefix = match token.occurance() {
+        Some(Occurance::Must) => "+",
+        Some(Occurance::MustNot) => "-",
+        None => "",
+    };
+    let with_field = |field: &Option<String>, value: String| match field {
+        Some(field) => format!("{}:{}", field, value),
+        None => value,
+    };
+    let text = match token {
+        Token::Text { strings, .. } => strings.join(" "),
+        Token::FilterEquals { field, value, .. } => format!("{}:{}", field, value),
+        Token::FilterExists {
+            field,
+            exists: true,
+            ..
+        } => format!("has:{}", field),
+        Token::FilterExists { field, .. } => format!("missing:{}", field),
+        Token::FilterIn { field, values, .. } => format!("{}:({})", field, values.join("|")),
+        Token::FilterRange {
+            field,
+            left_bound,
+            right_bound,
+            ..
+        } => {
+            let (left, left_value) = describe_bound(left_bound, '[', '{');
+            let (right, right_value) = describe_bound(right_bound, ']', '}');
+
+            format!("{}:{}{}..{}{}", field, left, left_value, right_value, right)
+        }
+        Token::Phrase {
+            field,
+            strings,
+            slop,
+            ..
+        } => {
+            let phrase = format!("\"{}\"", strings.join(" "));
+
+            if *slop > 0 {
+                with_field(field, format!("{}~{}", phrase, slop))
+            } else {
+                with_field(field, phrase)
+            }
+        }
+        Token::Wildcard { field, pattern, .. } => with_field(field, pattern.clone()),
+        Token::Fuzzy {
+            field,
+            string,
+            distance,
+            ..
+        } => with_field(field, format!("{}~{}", string, distance)),
+        Token::Regex { field, pattern, .. } => with_field(field, format!("/{}/", pattern)),
+        Token::Group { tokens, .. } => {
+            let tokens: Vec<_> = tokens.iter().map(describe).collect();
+
+            format!("({})", tokens.join(" "))
+        }
+        Token::Boost { token, boost } => return format!("{}^{}", describe(token), boost),
+    };
+
+    format!("{}{}", prefix, text)
+}
+
+fn describe_bound(bound: &RangeBound, included: char, excluded: char) -> (char, &str) {
+    match bound {
+        RangeBound::Included(value) => (included, value),
+        RangeBound::Excluded(value) => (excluded, value),
+        RangeBound::Unbounded => (included, ""),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    query: RequestQuery,
+    key: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    key: i64,
+    matched: bool,
+    score: Option<Score>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failures: Vec<String>,
+    clauses: Vec<Explanation>,
+}
+
+#[derive(Debug, Serialize)]
+struct Explanation {
+    clause: String,
+    occurance: Option<Occurance>,
+    matched: bool,
+    score: Option<Score>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    details: Vec<Explanation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+    use crate::parser::parse_query;
+
+    fn explain(query: &str, key: i64) -> Response {
+        let index = create_index(&[
+            &[
+                ("id", "1"),
+                ("level", "error"),
+                ("message", "connection refused"),
+            ],
+            &[
+                ("id", "2"),
+                ("level", "info"),
+                ("message", "connection timeout"),
+            ],
+            &[("id", "3"), ("level", "info"), ("message", "disk full")],
+        ]);
+        let handler = ExplainHandler::new(&index.config, &index.text_index);
+        let tokens = parse_query(query).unwrap();
+
+        index
+            .text_index
+            .read(|reader, schema| handler.process_query(reader, schema, &tokens, key))
+            .unwrap()
+            .unwrap()
+    }
+
+    fn summary(clauses: &[Explanation]) -> Vec<(&str, bool)> {
+        clauses
+            .iter()
+            .map(|clause| (clause.clause.as_str(), clause.matched))
+            .collect()
+    }
+
+    #[test]
+    fn should_explain_matched_document() {
+        let response = explain("+connection (refused timeout) -level:info", 1);
+
+        assert_eq!(response.matched, true);
+        assert_eq!(response.score.is_some(), true);
+        assert_eq!(response.failures.is_empty(), true);
+        assert_eq!(
+            summary(&response.clauses),
+            vec![
+                ("+connection", true),
+                ("(refused timeout)", true),
+                ("-level:info", false)
+            ]
+        );
+        assert_eq!(
+            summary(&response.clauses[1].details),
+            vec![("refused", true), ("timeout", false)]
+        );
+    }
+
+    #[test]
+    fn should_explain_failed_required_clause_inside_group() {
+        let response = explain("+(+connection +timeout) level:error", 1);
+
+        assert_eq!(response.matched, false);
+        assert_eq!(response.score, None);
+        assert_eq!(
+            response.failures,
+            vec![
+                "Required clause `+(+connection +timeout)` does not match",
+                "Required clause `+timeout` does not match"
+            ]
+        );
+        assert_eq!(
+            summary(&response.clauses[0].details),
+            vec![("+connection", true), ("+timeout", false)]
+        );
+    }
+
+    #[test]
+    fn should_explain_optional_and_excluded_clauses() {
+        assert_eq!(
+            explain("refused timeout", 3).failures,
+            vec!["None of clauses `refused`, `timeout` matches"]
+        );
+        assert_eq!(
+            explain("disk -level:info", 3).failures,
+            vec!["Excluded clause `-level:info` matches"]
+        );
+    }
+
+    #[test]
+    fn should_explain_boosted_clause() {
+        let plain = explain("connection refused", 1);
+        let boosted = explain("connection^2 refused", 1);
+
+        assert_eq!(boosted.matched, true);
+        assert_eq!(boosted.clauses[0].clause, "connection^2");
+        assert_eq!(
+            boosted.clauses[0].score,
+            plain.clauses[0].score.map(|score| score * 2.0)
+        );
+        assert_eq!(boosted.clauses[1].score, plain.clauses[1].score);
+    }
+
+    #[test]
+    fn should_describe_tokens() {
+        let tokens = parse_query(
+            "-id:{1..5] size:>=3 has:level \"disk full\"~2 mess*ge:x conection~1 /ti.*/ level:(a|b)",
+        )
+        .unwrap();
+        let descriptions: Vec<_> = tokens.iter().map(describe).collect();
+
+        assert_eq!(
+            descriptions,
+            vec![
+                "-id:{1..5]",
+                "size:[3..]",
+                "has:level",
+                "\"disk full\"~2",
+                "mess*ge:x",
+                "conection~1",
+                "/ti.*/",
+                "level:(a|b)"
+            ]
+        );
+    }
+}
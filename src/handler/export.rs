@@ -0,0 +1,356 @@
+use crate::collector::KeyCollector;
+use crate::config::ConfigRef;
+use crate::handler::builder::QueryBuilder;
+use crate::handler::query::RequestQuery;
+use crate::handler::util::document_to_map;
+use crate::handler::util::handle_stream;
+use crate::handler::util::key_field;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::index::TextIndexRef;
+use crate::parser::Token;
+use iron::headers::ContentType;
+use iron::method::Method;
+use iron::middleware::Handler;
+use iron::mime::Mime;
+use iron::mime::SubLevel;
+use iron::mime::TopLevel;
+use iron::response::WriteBody;
+use iron::status;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::collections::HashMap;
+use std::io::BufWriter;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Result as IoResult;
+use std::io::Write;
+use tantivy::schema::Field;
+use tantivy::IndexReader;
+use tantivy::ReloadPolicy;
+
+#[derive(Debug)]
+pub struct ExportHandler {
+    config: ConfigRef,
+    text_index: TextIndexRef,
+    query_builder: QueryBuilder,
+}
+
+impl ExportHandler {
+    pub fn new(config: &ConfigRef, text_index: &TextIndexRef) -> ExportHandler {
+        ExportHandler {
+            config: config.clone(),
+            text_index: text_index.clone(),
+            query_builder: QueryBuilder::new(config),
+        }
+    }
+
+    // Query is sent as JSON body of POST request or as `query` parameter of GET request.
+    fn parse_request(
+        &self,
+        request: &mut IronRequest,
+    ) -> HandlerResult<(ExportFormat, Vec<Token>)> {
+        let mut format = ExportFormat::Csv;
+        let mut query = None;
+
+        for (name, value) in request.url.as_ref().query_pairs() {
+            match name.as_ref() {
+                "format" => format = ExportFormat::parse(&value)?,
+                "query" => query = Some(RequestQuery::Text(value.into_owned())),
+                _ => {}
+            }
+        }
+
+        if request.method == Method::Post {
+            let body: Request = serde_json::from_reader(request.body.by_ref())
+                .map_err(|err| HandlerError::new(&format!("{}", err)))?;
+
+            query = Some(body.query);
+        }
+
+        let query = query.ok_or_else(|| HandlerError::new("Query not defined"))?;
+
+        Ok((format, query.to_tokens()?))
+    }
+
+    fn process_query(
+        &self,
+        format: ExportFormat,
+        tokens: Vec<Token>,
+    ) -> HandlerResult<IronResponse> {
+        let (reader, schema) = self
+            .text_index
+            .read(|reader, schema| (reader.clone(), schema.clone()))
+            .map_err(|err| HandlerError::new(&format!("{}", err)))?;
+        // Export can take a long time, so it reads its own state of index to not block searches.
+        let reader = reader
+            .searcher()
+            .index()
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .num_searchers(1)
+            .try_into()
+            .map_err(|err| HandlerError::new(&format!("Failed to open reader - {:?}", err)))?;
+        let key_field = key_field(&self.config, &schema)?;
+
+        // Query is built before streaming to report its errors, tantivy queries can not be sent to another thread.
+        self.query_builder
+            .build_query(&tokens, &schema, &reader.searcher())?;
+
+        let body = ExportBody {
+            config: self.config.clone(),
+            query_builder: self.query_builder.clone(),
+            reader,
+            schema,
+            tokens,
+            key_field,
+            format,
+        };
+        let mut response = IronResponse::with((status::Ok, Box::new(body) as Box<WriteBody>));
+
+        response.headers.set(ContentType(format.mime()));
+        response.headers.set_raw(
+            "Content-Disposition",
+            vec![format!("attachment; filename=\"export.{}\"", format.extension()).into_bytes()],
+        );
+
+        Ok(response)
+    }
+}
+
+impl Handler for ExportHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        handle_stream(move || {
+            let (format, tokens) = self.parse_request(request)?;
+
+            self.process_query(format, tokens)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn parse(value: &str) -> HandlerResult<ExportFormat> {
+        match value {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            _ => Err(HandlerError::new(&format!(
+                "Unknown export format `{}`",
+                value
+            ))),
+        }
+    }
+
+    fn mime(self) -> Mime {
+        match self {
+            ExportFormat::Csv => Mime(TopLevel::Text, SubLevel::Ext("csv".into()), vec![]),
+            ExportFormat::Ndjson => Mime(
+                TopLevel::Application,
+                SubLevel::Ext("x-ndjson".into()),
+                vec![],
+            ),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+// Only keys and addresses of matched documents are kept in memory, documents are read while writing.
+struct ExportBody {
+    config: ConfigRef,
+    query_builder: QueryBuilder,
+    reader: IndexReader,
+    schema: HashMap<String, Field>,
+    tokens: Vec<Token>,
+    key_field: Field,
+    format: ExportFormat,
+}
+
+impl ExportBody {
+    fn write_documents<W>(&self, writer: &mut W) -> HandlerResult<()>
+    where
+        W: Write,
+    {
+        let searcher = self.reader.searcher();
+        let index_schema = searcher.schema();
+        let query = self
+            .query_builder
+            .build_query(&self.tokens, &self.schema, &searcher)?;
+        let keys = searcher
+            .search(&query, &KeyCollector::new(self.key_field))
+            .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+
+        if let ExportFormat::Csv = self.format {
+            let header: Vec<_> = self
+                .config
+                .schema()
+                .iter()
+                .map(|field| csv_value(field.display()))
+                .collect();
+
+            writeln!(writer, "{}", header.join(",")).map_err(write_error)?;
+        }
+
+        for (_, address) in keys {
+            let retrieved_doc = searcher
+                .doc(address)
+                .map_err(|err| HandlerError::new(&format!("Search document - {:?}", err)))?;
+            let doc = document_to_map(index_schema, &retrieved_doc);
+
+            match self.format {
+                ExportFormat::Csv => {
+                    let row: Vec<_> = self
+                        .config
+                        .schema()
+                        .iter()
+                        .map(|field| match doc.get(field.name()) {
+                            Some(Some(value)) => csv_value(value),
+                            _ => String::new(),
+                        })
+                        .collect();
+
+                    writeln!(writer, "{}", row.join(",")).map_err(write_error)?;
+                }
+                ExportFormat::Ndjson => {
+                    serde_json::to_writer(&mut *writer, &doc)
+                        .map_err(|err| HandlerError::new(&format!("{}", err)))?;
+                    writeln!(writer).map_err(write_error)?;
+                }
+            }
+        }
+
+        writer.flush().map_err(write_error)
+    }
+}
+
+impl WriteBody for ExportBody {
+    fn write_body(&mut self, res: &mut Write) -> IoResult<()> {
+        let mut writer = BufWriter::new(res);
+
+        // Response is already started, so error can be only logged and connection is closed.
+        self.write_documents(&mut writer).map_err(|err| {
+            warn!("Failed to export documents - {}", err);
+
+            IoError::from(ErrorKind::Other)
+        })
+    }
+}
+
+// Values containing separators, quotes or line breaks are quoted.
+fn csv_value(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
+
+fn write_error(err: IoError) -> HandlerError {
+    HandlerError::new(&format!("Failed to write document - {}", err))
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    query: RequestQuery,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+    use crate::parser::parse_query;
+
+    fn export(query: &str, format: ExportFormat) -> String {
+        // Documents are written by two commits, so keys of both segments are merged.
+        let mut index = create_index(&[
+            &[
+                ("id", "3"),
+                ("level", "error"),
+                ("message", "connection refused"),
+            ],
+            &[("id", "-7"), ("message", "connection \"reset\", retry")],
+        ]);
+
+        index.add_documents(&[
+            &[("id", "1"), ("level", "info"), ("message", "timeout")],
+            &[("id", "2"), ("level", "error"), ("message", "disk full")],
+        ]);
+        let (reader, schema) = index
+            .text_index
+            .read(|reader, schema| (reader.clone(), schema.clone()))
+            .unwrap();
+        let body = ExportBody {
+            config: index.config.clone(),
+            query_builder: QueryBuilder::new(&index.config),
+            reader,
+            key_field: schema["id"],
+            schema,
+            tokens: parse_query(query).unwrap(),
+            format,
+        };
+        let mut result = Vec::new();
+
+        body.write_documents(&mut result).unwrap();
+        String::from_utf8(result).unwrap()
+    }
+
+    fn keys(query: &str) -> Vec<String> {
+        export(query, ExportFormat::Ndjson)
+            .lines()
+            .map(|line| {
+                let doc: HashMap<String, String> = serde_json::from_str(line).unwrap();
+
+                doc["id"].clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_export_documents_ordered_by_key() {
+        assert_eq!(
+            keys("connection OR timeout OR disk"),
+            vec!["-7", "1", "2", "3"]
+        );
+        assert_eq!(keys("+level:error"), vec!["2", "3"]);
+        assert_eq!(keys("-level:error"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn should_export_csv() {
+        assert_eq!(
+            export("connection", ExportFormat::Csv),
+            "Id,Size,Rev,Level,Message\n\
+             -7,,,,\"connection \"\"reset\"\", retry\"\n\
+             3,,,error,connection refused\n"
+        );
+    }
+
+    #[test]
+    fn should_keep_plain_csv_value() {
+        assert_eq!(csv_value(""), "");
+        assert_eq!(
+            csv_value("connection refused; retry"),
+            "connection refused; retry"
+        );
+    }
+
+    #[test]
+    fn should_quote_csv_value() {
+        assert_eq!(csv_value("a,b"), "\"a,b\"");
+        assert_eq!(csv_value("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_value("a\rb"), "\"a\rb\"");
+        assert_eq!(csv_value("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -41,13 +41,17 @@ struct ResponseField {
     display: String,
     description: String,
     data_type: String,
+    sortable: bool,
+    facet: bool,
 }
 
 impl ResponseField {
     fn from_field(field: &FieldConfig) -> Self {
-        let data_type = match field.data_type() {
-            DataType::Int { .. } | DataType::UInt { .. } => "number",
-            DataType::Text => "string",
+        let (data_type, sortable) = match field.data_type() {
+            DataType::Int { sortable, .. } | DataType::UInt { sortable, .. } => {
+                ("number", sortable)
+            }
+            DataType::Text => ("string", false),
         };
 
         ResponseField {
@@ -55,6 +59,8 @@ impl ResponseField {
             display: field.display().into(),
             description: field.description().into(),
             data_type: data_type.into(),
+            sortable,
+            facet: field.facet(),
         }
     }
 }
@@ -1,9 +1,27 @@
+mod builder;
+mod context;
+mod cursor;
+mod document;
 mod error;
+mod explain;
+mod export;
 mod fields;
+mod parse;
+mod query;
 mod search;
+mod similar;
+mod spelling;
+mod suggest;
 mod util;
 
+pub use self::context::ContextHandler;
+pub use self::document::DocumentHandler;
 pub use self::error::HandlerError;
 pub use self::error::HandlerResult;
+pub use self::explain::ExplainHandler;
+pub use self::export::ExportHandler;
 pub use self::fields::FieldsHandler;
+pub use self::parse::ParseHandler;
 pub use self::search::SearchHandler;
+pub use self::similar::SimilarHandler;
+pub use self::suggest::SuggestHandler;
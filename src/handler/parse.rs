@@ -0,0 +1,224 @@
+use crate::config::ConfigRef;
+use crate::handler::builder::QueryBuilder;
+use crate::handler::query::RequestQuery;
+use crate::handler::util::handle_request;
+use crate::parser::parse_query_with_positions;
+use crate::parser::Token;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::collections::BTreeMap;
+use std::vec::IntoIter;
+
+#[derive(Debug)]
+pub struct ParseHandler {
+    query_builder: QueryBuilder,
+}
+
+impl ParseHandler {
+    pub fn new(config: &ConfigRef) -> ParseHandler {
+        ParseHandler {
+            query_builder: QueryBuilder::new(config),
+        }
+    }
+
+    fn process_query(&self, query: &RequestQuery) -> Response {
+        let (tokens, positions) = match query {
+            RequestQuery::Text(query) => match parse_query_with_positions(query) {
+                Ok(result) => result,
+                Err(err) => {
+                    return Response {
+                        tokens: None,
+                        fields: BTreeMap::new(),
+                        problems: vec![Problem {
+                            message: err.message().into(),
+                            position: Some(err.position()),
+                        }],
+                    };
+                }
+            },
+            RequestQuery::Structured(query) => (query.to_tokens(), Vec::new()),
+        };
+        let mut validator = Validator {
+            query_builder: &self.query_builder,
+            positions: positions.into_iter(),
+            fields: BTreeMap::new(),
+            problems: Vec::new(),
+        };
+
+        validator.check_tokens(&tokens);
+
+        Response {
+            tokens: Some(tokens),
+            fields: validator.fields,
+            problems: validator.problems,
+        }
+    }
+}
+
+impl Handler for ParseHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        handle_request(request, move |request: Request| {
+            Ok(self.process_query(&request.query))
+        })
+    }
+}
+
+// Runs the same checks as query builder, but collects all problems instead of stopping on first one.
+struct Validator<'a> {
+    query_builder: &'a QueryBuilder,
+    positions: IntoIter<usize>,
+    fields: BTreeMap<String, String>,
+    problems: Vec<Problem>,
+}
+
+impl<'a> Validator<'a> {
+    fn check_tokens(&mut self, tokens: &[Token]) {
+        for token in tokens {
+            self.check_token(token);
+        }
+    }
+
+    fn check_token(&mut self, token: &Token) {
+        match token {
+            Token::Group { tokens, .. } => return self.check_tokens(tokens),
+            Token::Boost { token, .. } => return self.check_token(token),
+            _ => {}
+        }
+
+        // Every other token is a single term of query string.
+        let position = self.positions.next();
+        let field = match token {
+            Token::FilterEquals { field, .. }
+            | Token::FilterExists { field, .. }
+            | Token::FilterIn { field, .. }
+            | Token::FilterRange { field, .. } => Some(field),
+            Token::Phrase { field, .. }
+            | Token::Wildcard { field, .. }
+            | Token::Fuzzy { field, .. }
+            | Token::Regex { field, .. } => field.as_ref(),
+            _ => None,
+        };
+
+        if let Some(field) = field {
+            if let Ok((kind, _)) = self.query_builder.field_kind(field) {
+                self.fields.insert(field.clone(), kind.name().into());
+            }
+        }
+
+        if let Err(err) = self.query_builder.check_token(token) {
+            self.problems.push(Problem {
+                message: format!("{}", err),
+                position,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    query: RequestQuery,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    tokens: Option<Vec<Token>>,
+    fields: BTreeMap<String, String>,
+    problems: Vec<Problem>,
+}
+
+#[derive(Debug, Serialize)]
+struct Problem {
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+
+    fn parse(query: &str) -> Response {
+        let index = create_index(&[]);
+        let handler = ParseHandler::new(&index.config);
+
+        handler.process_query(&RequestQuery::Text(query.into()))
+    }
+
+    fn problems(response: &Response) -> Vec<(&str, Option<usize>)> {
+        response
+            .problems
+            .iter()
+            .map(|problem| (problem.message.as_str(), problem.position))
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_valid_query() {
+        let response = parse("id:>10 (level:error OR message:\"connection refused\")^2 time*");
+
+        assert_eq!(response.tokens.is_some(), true);
+        assert_eq!(response.problems.is_empty(), true);
+        assert_eq!(
+            response.fields.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("id".into(), "int".into()),
+                ("level".into(), "text".into()),
+                ("message".into(), "text".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_syntax_error_position() {
+        let response = parse("level:error AND (message:timeout");
+
+        assert_eq!(response.tokens.is_none(), true);
+        assert_eq!(response.problems.len(), 1);
+        assert_eq!(response.problems[0].position, Some(32));
+    }
+
+    #[test]
+    fn should_report_all_problems_with_positions() {
+        let response = parse("rev:5 size:abc level:>5 other:1 message:/x/ size:a*");
+
+        assert_eq!(response.tokens.is_some(), true);
+        assert_eq!(
+            problems(&response),
+            vec![
+                ("Field `rev` not indexed", Some(0)),
+                (
+                    "Failed to parse value `abc` - invalid digit found in string",
+                    Some(6)
+                ),
+                ("Field `level` not numeric", Some(15)),
+                ("Field `other` not defined", Some(24)),
+                ("Field `size` not text", Some(44)),
+            ]
+        );
+        assert_eq!(
+            response.fields.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("level".into(), "text".into()),
+                ("message".into(), "text".into()),
+                ("rev".into(), "uint".into()),
+                ("size".into(), "int".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_value_problems_without_words() {
+        let response = parse("message:\"...\" level:(error|--)");
+
+        assert_eq!(
+            problems(&response),
+            vec![
+                ("Value `...` does not contain any words", Some(0)),
+                ("Value `--` does not contain any words", Some(14)),
+            ]
+        );
+    }
+}
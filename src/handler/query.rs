@@ -0,0 +1,94 @@
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::parser::parse_query;
+use crate::parser::StructuredQuery;
+use crate::parser::Token;
+use serde::de::Error;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde_json::Value;
+
+#[derive(Debug)]
+pub enum RequestQuery {
+    Text(String),
+    Structured(StructuredQuery),
+}
+
+impl RequestQuery {
+    pub fn to_tokens(&self) -> HandlerResult<Vec<Token>> {
+        match self {
+            RequestQuery::Text(query) => {
+                parse_query(query).map_err(|err| HandlerError::new(&format!("{}", err)))
+            }
+            RequestQuery::Structured(query) => Ok(query.to_tokens()),
+        }
+    }
+}
+
+// Untagged enum hides errors of structured query, so variant is selected by shape of value.
+impl<'de> Deserialize<'de> for RequestQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::String(query) => Ok(RequestQuery::Text(query)),
+            value @ Value::Object(_) => StructuredQuery::deserialize(value)
+                .map(RequestQuery::Structured)
+                .map_err(D::Error::custom),
+            _ => Err(D::Error::custom("query must be a string or an object")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<RequestQuery, String> {
+        serde_json::from_str(json).map_err(|err| format!("{}", err))
+    }
+
+    #[test]
+    fn should_deserialize_text_query() {
+        let query = parse(r#""+level:error""#).unwrap();
+
+        assert_eq!(query.to_tokens().unwrap().len(), 1);
+        assert_eq!(
+            match query {
+                RequestQuery::Text(query) => query,
+                _ => String::new(),
+            },
+            "+level:error"
+        );
+    }
+
+    #[test]
+    fn should_deserialize_structured_query() {
+        let query = parse(r#"{ "term": { "field": "level", "value": "error" } }"#);
+
+        assert_eq!(query.is_ok(), true);
+        assert_eq!(query.unwrap().to_tokens().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_report_structured_query_error() {
+        let error = parse(r#"{ "bool": { "must": [{ "term": { "field": "level" } }] } }"#);
+
+        assert_eq!(error.unwrap_err(), "missing field `value`");
+        assert_eq!(
+            parse(r#"{ "match": { "value": "error" } }"#)
+                .unwrap_err()
+                .starts_with("unknown variant `match`"),
+            true
+        );
+    }
+
+    #[test]
+    fn should_fail_when_query_has_other_type() {
+        assert_eq!(
+            parse("42").unwrap_err(),
+            "query must be a string or an object"
+        );
+    }
+}
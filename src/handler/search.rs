@@ -1,37 +1,49 @@
+use crate::collector::Aggregation;
+use crate::collector::AggregationCollector;
+use crate::collector::AggregationFruit;
+use crate::collector::AggregationKind;
+use crate::collector::FacetCountCollector;
+use crate::collector::FastField;
+use crate::collector::SortCollector;
+use crate::collector::SortOrder;
 use crate::config::ConfigRef;
+use crate::config::DataType;
+use crate::handler::builder::QueryBuilder;
+use crate::handler::cursor::Cursor;
+use crate::handler::cursor::PinnedReaders;
+use crate::handler::query::RequestQuery;
+use crate::handler::spelling::correct_query;
+use crate::handler::util::document_to_map;
 use crate::handler::util::handle_request;
+use crate::handler::util::key_field;
 use crate::handler::HandlerError;
 use crate::handler::HandlerResult;
+use crate::index::facet_field_name;
 use crate::index::TextIndexRef;
-use crate::parser::parse_query;
-use crate::parser::Occurance;
+use crate::index::PRESENT_FIELD;
 use crate::parser::Token;
 use iron::middleware::Handler;
 use iron::IronResult;
 use iron::Request as IronRequest;
 use iron::Response as IronResponse;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::fmt::Display;
-use std::ops::Bound;
-use std::str::FromStr;
-use tantivy::collector::TopDocs;
-use tantivy::query::BooleanQuery;
-use tantivy::query::Occur;
+use tantivy::collector::Count;
 use tantivy::query::Query;
-use tantivy::query::RangeQuery;
-use tantivy::query::TermQuery;
 use tantivy::schema::Field;
 use tantivy::schema::FieldType;
-use tantivy::schema::IndexRecordOption;
-use tantivy::schema::Schema;
-use tantivy::schema::Value;
 use tantivy::IndexReader;
+use tantivy::Searcher;
+use tantivy::Snippet;
+use tantivy::SnippetGenerator;
 use tantivy::Term;
 
 #[derive(Debug)]
 pub struct SearchHandler {
     config: ConfigRef,
     text_index: TextIndexRef,
+    query_builder: QueryBuilder,
+    pinned_readers: PinnedReaders,
 }
 
 impl SearchHandler {
@@ -39,6 +51,8 @@ impl SearchHandler {
         SearchHandler {
             config: config.clone(),
             text_index: text_index.clone(),
+            query_builder: QueryBuilder::new(config),
+            pinned_readers: PinnedReaders::new(config.search().cursor_timeout()),
         }
     }
 
@@ -47,249 +61,612 @@ impl SearchHandler {
         reader: &IndexReader,
         schema: &HashMap<String, Field>,
         tokens: &[Token],
-    ) -> HandlerResult<Vec<HashMap<String, Option<String>>>> {
-        let mut result = Vec::new();
+        request: &Request,
+        limit: usize,
+        facet_size: usize,
+    ) -> HandlerResult<Response> {
+        let mut hits = Vec::new();
+        let cursor_request = (tokens, &request.sort);
+        let cursor = match &request.cursor {
+            Some(cursor) => Some(Cursor::decode(cursor)?),
+            None => None,
+        };
+
+        if let Some(cursor) = &cursor {
+            cursor.check_request(&cursor_request)?;
+        }
+
+        // Only the next pages are read from pinned reader, the first page is read from the last commit.
+        let reader = match &cursor {
+            Some(cursor) => self.pinned_readers.get(cursor)?,
+            None => reader.clone(),
+        };
         let searcher = reader.searcher();
         let index_schema = searcher.schema();
-        let query = self.build_query(tokens, schema, index_schema)?;
-        let top_docs = searcher
-            .search(&query, &TopDocs::with_limit(50))
-            .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+        let query = self.query_builder.build_query(tokens, schema, &searcher)?;
+        let sort_fields = self.sort_fields(&request.sort, schema, &searcher)?;
+        let facet_fields = self.facet_fields(&request.facets, schema)?;
+        let aggregations = self.aggregations(&request.aggregations, schema, &searcher)?;
+        let key_field = key_field(&self.config, schema)?;
+        let offset = request.offset;
+        // One more document is collected to find out if the next page exists.
+        let mut collector = SortCollector::new(sort_fields, key_field, offset + limit + 1);
+
+        if let Some(cursor) = &cursor {
+            collector = collector.after(cursor.position().clone());
+        }
 
-        for (_score, doc_address) in top_docs {
+        let facet_collector = FacetCountCollector::new(facet_fields);
+        let aggregation_collector = AggregationCollector::new(aggregations);
+        let (total, sorted_docs, facet_counts, aggregation_fruits) = searcher
+            .search(
+                &query,
+                &(Count, collector, facet_collector, aggregation_collector),
+            )
+            .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+        let facets = request
+            .facets
+            .iter()
+            .cloned()
+            .zip(facet_counts)
+            .map(|(name, counts)| (name, top_facet_values(counts, facet_size)))
+            .collect();
+        let aggregations = request
+            .aggregations
+            .iter()
+            .zip(aggregation_fruits)
+            .map(|((name, aggregation), fruit)| {
+                (name.clone(), aggregation_result(aggregation, fruit))
+            })
+            .collect();
+        let suggested_query = match &request.query {
+            RequestQuery::Text(query) if total == 0 => {
+                correct_query(query, &searcher, &self.text_fields(schema)?)?
+            }
+            _ => None,
+        };
+        let mut sorted_docs: Vec<_> = sorted_docs.into_iter().skip(offset).collect();
+        let next_cursor = if sorted_docs.len() > limit {
+            let generation = self.pinned_readers.pin(&searcher)?;
+
+            sorted_docs.truncate(limit);
+            sorted_docs.last().map(|sorted_doc| {
+                Cursor::new(generation, &cursor_request, sorted_doc.position().clone()).encode()
+            })
+        } else {
+            None
+        };
+
+        let snippet_generators = match &request.highlight {
+            Some(highlight) => self.snippet_generators(schema, &searcher, &query, highlight)?,
+            None => Vec::new(),
+        };
+        let mut highlights = Vec::new();
+
+        for sorted_doc in &sorted_docs {
             let retrieved_doc = searcher
-                .doc(doc_address)
+                .doc(sorted_doc.address())
                 .map_err(|err| HandlerError::new(&format!("Search document - {:?}", err)))?;
-            let mut doc = HashMap::new();
 
-            for field_value in retrieved_doc.field_values() {
-                let field = field_value.field();
-                let name = index_schema.get_field_name(field);
-                let value = field_value.value();
+            hits.push(document_to_map(index_schema, &retrieved_doc));
 
-                doc.insert(name.into(), value_to_string(value));
-            }
+            if let Some(highlight) = &request.highlight {
+                let mut snippets = HashMap::new();
+
+                for (name, generator) in &snippet_generators {
+                    let snippet = generator.snippet_from_doc(&retrieved_doc);
+
+                    if !snippet.highlighted().is_empty() {
+                        snippets.insert(name.clone(), highlight_snippet(&snippet, highlight));
+                    }
+                }
 
-            result.push(doc);
+                highlights.push(snippets);
+            }
         }
 
-        Ok(result)
+        Ok(Response {
+            total,
+            offset,
+            limit,
+            hits,
+            highlights,
+            facets,
+            aggregations,
+            cursor: next_cursor,
+            suggested_query,
+        })
     }
 
-    fn build_query(
+    fn facet_fields(
         &self,
-        tokens: &[Token],
+        facets: &[String],
         field_schema: &HashMap<String, Field>,
-        index_schema: &Schema,
-    ) -> HandlerResult<Box<Query>> {
-        let mut text_fields = HashMap::new();
-        let mut u64_fields = HashMap::new();
-        let mut i64_fields = HashMap::new();
-
-        for entry in index_schema
-            .fields()
-            .iter()
-            .filter(|entry| entry.is_indexed())
-        {
-            let name = entry.name();
-
-            if let Some(field) = field_schema.get(name).cloned() {
-                match entry.field_type() {
-                    FieldType::Str(..) => {
-                        text_fields.insert(name, field);
-                    }
-                    FieldType::U64(..) => {
-                        u64_fields.insert(name, field);
-                    }
-                    FieldType::I64(..) => {
-                        i64_fields.insert(name, field);
-                    }
-                    _ => {}
-                }
+    ) -> HandlerResult<Vec<Field>> {
+        let mut result = Vec::new();
+
+        for name in facets {
+            let field_config = self
+                .config
+                .schema()
+                .iter()
+                .find(|field| field.name() == name)
+                .ok_or_else(|| HandlerError::new(&format!("Field `{}` not defined", name)))?;
+
+            if !field_config.facet() {
+                return Err(HandlerError::new(&format!("Field `{}` not faceted", name)));
             }
+
+            let field = field_schema
+                .get(&facet_field_name(name))
+                .cloned()
+                .ok_or_else(|| {
+                    HandlerError::new(&format!("Index does not contain facets of `{}`", name))
+                })?;
+
+            result.push(field);
         }
 
-        let mut terms: Vec<(_, Box<Query>)> = Vec::new();
+        Ok(result)
+    }
 
-        for token in tokens {
-            match token {
-                Token::Text { occurance, strings } => {
-                    let occur = self.map_occurance(occurance);
+    fn text_fields(&self, field_schema: &HashMap<String, Field>) -> HandlerResult<Vec<Field>> {
+        let mut result = Vec::new();
 
-                    for string in strings {
-                        let string = string.to_lowercase();
-                        let inner_terms: Vec<_> = text_fields
-                            .values()
-                            .cloned()
-                            .map(|field| {
-                                (Occur::Should, self.create_term_query_text(field, &string))
-                            })
-                            .collect();
+        for field_config in self.config.schema() {
+            if let DataType::Text = field_config.data_type() {
+                let field = field_schema
+                    .get(field_config.name())
+                    .cloned()
+                    .ok_or_else(|| {
+                        HandlerError::new(&format!("Field `{}` not defined", field_config.name()))
+                    })?;
 
-                        terms.push((occur, Box::new(BooleanQuery::from(inner_terms))));
-                    }
-                }
-                Token::FilterEquals {
-                    occurance,
-                    field,
-                    value,
-                } => {
-                    let occur = self.map_occurance(occurance);
-
-                    if let Some(field) = text_fields.get(field.as_str()).cloned() {
-                        let value = value.to_lowercase();
-
-                        terms.push((occur, self.create_term_query_text(field, &value)));
-                    } else if let Some(field) = u64_fields.get(field.as_str()).cloned() {
-                        let value = value.parse().map_err(|err| {
-                            HandlerError::new(&format!(
-                                "Failed to parse value `{}` - {}",
-                                value, err
-                            ))
-                        })?;
-
-                        terms.push((occur, self.create_term_query_u64(field, value)));
-                    } else if let Some(field) = i64_fields.get(field.as_str()).cloned() {
-                        let value = value.parse().map_err(|err| {
-                            HandlerError::new(&format!(
-                                "Failed to parse value `{}` - {}",
-                                value, err
-                            ))
-                        })?;
-
-                        terms.push((occur, self.create_term_query_i64(field, value)));
-                    } else {
-                        return Err(HandlerError::new(&format!("Field `{}` not defined", field)));
-                    }
-                }
-                Token::FilterRange {
-                    occurance,
-                    field,
-                    left_bound,
-                    right_bound,
-                } => {
-                    let occur = self.map_occurance(occurance);
-
-                    if let Some(field) = u64_fields.get(field.as_str()).cloned() {
-                        let left_bound = self.parse_bound(&left_bound)?;
-                        let right_bound = self.parse_bound(&right_bound)?;
-
-                        terms.push((
-                            occur,
-                            self.create_bound_query_u64(field, left_bound, right_bound),
-                        ));
-                    } else if let Some(field) = i64_fields.get(field.as_str()).cloned() {
-                        let left_bound = self.parse_bound(&left_bound)?;
-                        let right_bound = self.parse_bound(&right_bound)?;
-
-                        terms.push((
-                            occur,
-                            self.create_bound_query_i64(field, left_bound, right_bound),
-                        ));
-                    } else {
-                        return Err(HandlerError::new(&format!("Field `{}` not numeric", field)));
-                    }
-                }
+                result.push(field);
             }
         }
 
-        Ok(Box::new(BooleanQuery::from(terms)))
+        Ok(result)
     }
 
-    fn map_occurance(&self, occurance: &Option<Occurance>) -> Occur {
-        match occurance {
-            Some(Occurance::Must) => Occur::Must,
-            Some(Occurance::MustNot) => Occur::MustNot,
-            None => Occur::Should,
+    fn snippet_generators(
+        &self,
+        field_schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+        query: &Query,
+        highlight: &RequestHighlight,
+    ) -> HandlerResult<Vec<(String, SnippetGenerator)>> {
+        let mut result = Vec::new();
+
+        for field_config in self.config.schema() {
+            if let DataType::Text = field_config.data_type() {
+                let field = field_schema
+                    .get(field_config.name())
+                    .cloned()
+                    .ok_or_else(|| {
+                        HandlerError::new(&format!("Field `{}` not defined", field_config.name()))
+                    })?;
+                let mut generator = SnippetGenerator::create(searcher, query, field)
+                    .map_err(|err| HandlerError::new(&format!("Snippet error - {:?}", err)))?;
+
+                generator.set_max_num_chars(highlight.fragment_size);
+                result.push((field_config.name().into(), generator));
+            }
         }
+
+        Ok(result)
     }
 
-    fn parse_bound<T, E>(&self, value: &Option<String>) -> HandlerResult<Bound<T>>
-    where
-        T: FromStr<Err = E>,
-        E: Display,
-    {
-        match value {
-            Some(value) if value.is_empty() => Ok(Bound::Unbounded),
-            Some(value) => {
-                let value = value.parse().map_err(|err| {
-                    HandlerError::new(&format!("Failed to parse value `{}` - {}", value, err))
+    fn sort_fields(
+        &self,
+        sort: &[RequestSort],
+        field_schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+    ) -> HandlerResult<Vec<(FastField, SortOrder)>> {
+        let mut result = Vec::new();
+
+        for entry in sort {
+            let field = self
+                .fast_field(&entry.field, field_schema, searcher)?
+                .ok_or_else(|| {
+                    HandlerError::new(&format!("Field `{}` not sortable", entry.field))
                 })?;
 
-                Ok(Bound::Included(value))
-            }
-            None => Ok(Bound::Unbounded),
+            result.push((field, entry.order));
         }
-    }
 
-    fn create_bound_query_i64(
-        &self,
-        field: Field,
-        left_bound: Bound<i64>,
-        right_bound: Bound<i64>,
-    ) -> Box<Query> {
-        Box::new(RangeQuery::new_i64_bounds(field, left_bound, right_bound))
+        Ok(result)
     }
 
-    fn create_bound_query_u64(
+    fn aggregations(
         &self,
-        field: Field,
-        left_bound: Bound<u64>,
-        right_bound: Bound<u64>,
-    ) -> Box<Query> {
-        Box::new(RangeQuery::new_u64_bounds(field, left_bound, right_bound))
-    }
+        aggregations: &BTreeMap<String, RequestAggregation>,
+        field_schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+    ) -> HandlerResult<Vec<Aggregation>> {
+        let present_field = field_schema
+            .get(PRESENT_FIELD)
+            .cloned()
+            .ok_or_else(|| HandlerError::new("Index does not contain present fields"))?;
+        let mut result = Vec::new();
 
-    fn create_term_query_i64(&self, field: Field, value: i64) -> Box<Query> {
-        Box::new(TermQuery::new(
-            Term::from_field_i64(field, value),
-            IndexRecordOption::WithFreqs,
-        ))
-    }
+        for aggregation in aggregations.values() {
+            let (name, kind) = match aggregation {
+                RequestAggregation::Histogram { field, interval } => {
+                    if *interval == 0 {
+                        return Err(HandlerError::new(&format!(
+                            "Histogram interval of `{}` must be greater than zero",
+                            field
+                        )));
+                    }
+
+                    (field, AggregationKind::Histogram(i128::from(*interval)))
+                }
+                RequestAggregation::Range { field, ranges } => {
+                    let ranges = ranges
+                        .iter()
+                        .map(|range| (range.from.map(i128::from), range.to.map(i128::from)))
+                        .collect();
+
+                    (field, AggregationKind::Range(ranges))
+                }
+                RequestAggregation::Stats { field } => (field, AggregationKind::Stats),
+            };
+            let field = self
+                .fast_field(name, field_schema, searcher)?
+                .ok_or_else(|| HandlerError::new(&format!("Field `{}` not aggregatable", name)))?;
+            let present = Term::from_field_text(present_field, name);
+
+            result.push(Aggregation::new(field, present, kind));
+        }
 
-    fn create_term_query_u64(&self, field: Field, value: u64) -> Box<Query> {
-        Box::new(TermQuery::new(
-            Term::from_field_u64(field, value),
-            IndexRecordOption::WithFreqs,
-        ))
+        Ok(result)
     }
 
-    fn create_term_query_text(&self, field: Field, value: &str) -> Box<Query> {
-        Box::new(TermQuery::new(
-            Term::from_field_text(field, value),
-            IndexRecordOption::WithFreqs,
-        ))
+    // Returns numeric fast field, fields without fast values can not be sorted or aggregated.
+    fn fast_field(
+        &self,
+        name: &str,
+        field_schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+    ) -> HandlerResult<Option<FastField>> {
+        let field = field_schema
+            .get(name)
+            .cloned()
+            .ok_or_else(|| HandlerError::new(&format!("Field `{}` not defined", name)))?;
+        let index_schema = searcher.schema();
+        let field_entry = index_schema.get_field_entry(field);
+
+        Ok(match field_entry.field_type() {
+            FieldType::I64(..) if field_entry.is_int_fast() => Some(FastField::I64(field)),
+            FieldType::U64(..) if field_entry.is_int_fast() => Some(FastField::U64(field)),
+            _ => None,
+        })
     }
 }
 
 impl Handler for SearchHandler {
     fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
         handle_request(request, move |request: Request| {
-            let tokens = parse_query(&request.query)
-                .map_err(|err| HandlerError::new(&format!("{}", err)))?;
+            let tokens = request.query.to_tokens()?;
+            let search_config = self.config.search();
+            let offset = request.offset;
+            let limit = request
+                .limit
+                .unwrap_or_else(|| search_config.default_limit());
+            let facet_size = request
+                .facet_size
+                .unwrap_or_else(|| search_config.facet_size());
+
+            if limit == 0 || limit > search_config.max_limit() {
+                return Err(HandlerError::new(&format!(
+                    "Limit must be from 1 to {}, but {} given",
+                    search_config.max_limit(),
+                    limit
+                )));
+            }
+
+            if facet_size == 0 || facet_size > search_config.max_limit() {
+                return Err(HandlerError::new(&format!(
+                    "Facet size must be from 1 to {}, but {} given",
+                    search_config.max_limit(),
+                    facet_size
+                )));
+            }
+
+            if offset > 0 && request.cursor.is_some() {
+                return Err(HandlerError::new("Offset can not be used with cursor"));
+            }
+
+            if offset > search_config.max_offset() {
+                return Err(HandlerError::new(&format!(
+                    "Offset must not be greater than {}, but {} given",
+                    search_config.max_offset(),
+                    offset
+                )));
+            }
 
             if !tokens.is_empty() {
                 Ok(self
                     .text_index
-                    .read(|reader, schema| self.process_query(reader, schema, &tokens))
+                    .read(|reader, schema| {
+                        self.process_query(reader, schema, &tokens, &request, limit, facet_size)
+                    })
                     .map_err(|err| HandlerError::new(&format!("{}", err)))??)
             } else {
-                Ok(Vec::new())
+                Ok(Response {
+                    total: 0,
+                    offset,
+                    limit,
+                    hits: Vec::new(),
+                    highlights: Vec::new(),
+                    facets: BTreeMap::new(),
+                    aggregations: BTreeMap::new(),
+                    cursor: None,
+                    suggested_query: None,
+                })
             }
         })
     }
 }
 
-fn value_to_string(value: &Value) -> Option<String> {
-    match value {
-        Value::Str(value) => Some(value.clone()),
-        Value::U64(value) => Some(value.to_string()),
-        Value::I64(value) => Some(value.to_string()),
-        _ => None,
+// Most frequent values go first, values with equal counts are ordered by value.
+fn top_facet_values(counts: HashMap<String, u64>, size: usize) -> Vec<FacetValue> {
+    let mut values: Vec<_> = counts
+        .into_iter()
+        .map(|(value, count)| FacetValue { value, count })
+        .collect();
+
+    values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    values.truncate(size);
+    values
+}
+
+fn aggregation_result(
+    aggregation: &RequestAggregation,
+    fruit: AggregationFruit,
+) -> AggregationResult {
+    match (aggregation, fruit) {
+        (_, AggregationFruit::Histogram(buckets)) => AggregationResult::Buckets(
+            buckets
+                .into_iter()
+                .map(|(key, count)| HistogramBucket { key, count })
+                .collect(),
+        ),
+        (RequestAggregation::Range { ranges, .. }, AggregationFruit::Range(counts)) => {
+            AggregationResult::Ranges(
+                ranges
+                    .iter()
+                    .zip(counts)
+                    .map(|(range, count)| RangeBucket {
+                        from: range.from,
+                        to: range.to,
+                        count,
+                    })
+                    .collect(),
+            )
+        }
+        (_, AggregationFruit::Range(..)) => AggregationResult::Ranges(Vec::new()),
+        (_, AggregationFruit::Stats(stats)) => AggregationResult::Stats {
+            count: stats.count(),
+            min: stats.min(),
+            max: stats.max(),
+            sum: stats.sum(),
+            avg: if stats.count() > 0 {
+                Some(stats.sum() as f64 / stats.count() as f64)
+            } else {
+                None
+            },
+        },
     }
 }
 
+// Text of snippet is escaped, highlighted parts are surrounded by requested tags.
+fn highlight_snippet(snippet: &Snippet, highlight: &RequestHighlight) -> String {
+    let fragments = snippet.fragments();
+    let mut result = String::new();
+    let mut start = 0;
+
+    for section in snippet.highlighted() {
+        let (section_start, section_end) = section.bounds();
+
+        result.push_str(&escape_html(&fragments[start..section_start]));
+        result.push_str(&highlight.pre_tag);
+        result.push_str(&escape_html(&fragments[section_start..section_end]));
+        result.push_str(&highlight.post_tag);
+        start = section_end;
+    }
+
+    result.push_str(&escape_html(&fragments[start..]));
+    result
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '&' => "&amp;".into(),
+            '<' => "&lt;".into(),
+            '>' => "&gt;".into(),
+            '"' => "&quot;".into(),
+            '\'' => "&#39;".into(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
 #[derive(Debug, Deserialize)]
 struct Request {
-    query: String,
+    query: RequestQuery,
+    #[serde(default)]
+    sort: Vec<RequestSort>,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+    cursor: Option<String>,
+    highlight: Option<RequestHighlight>,
+    #[serde(default)]
+    facets: Vec<String>,
+    facet_size: Option<usize>,
+    #[serde(default)]
+    aggregations: BTreeMap<String, RequestAggregation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestSort {
+    field: String,
+    #[serde(default)]
+    order: SortOrder,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RequestAggregation {
+    Histogram {
+        field: String,
+        interval: u64,
+    },
+    Range {
+        field: String,
+        ranges: Vec<RequestRange>,
+    },
+    Stats {
+        field: String,
+    },
+}
+
+// Range includes `from` and excludes `to`, missing bound is unbounded.
+#[derive(Debug, Deserialize)]
+struct RequestRange {
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct RequestHighlight {
+    fragment_size: usize,
+    pre_tag: String,
+    post_tag: String,
+}
+
+impl Default for RequestHighlight {
+    fn default() -> Self {
+        RequestHighlight {
+            fragment_size: 150,
+            pre_tag: "<b>".into(),
+            post_tag: "</b>".into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    total: usize,
+    offset: usize,
+    limit: usize,
+    hits: Vec<HashMap<String, Option<String>>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    highlights: Vec<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    facets: BTreeMap<String, Vec<FacetValue>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    aggregations: BTreeMap<String, AggregationResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggested_query: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct FacetValue {
+    value: String,
+    count: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum AggregationResult {
+    Buckets(Vec<HistogramBucket>),
+    Ranges(Vec<RangeBucket>),
+    Stats {
+        count: u64,
+        min: Option<i128>,
+        max: Option<i128>,
+        sum: i128,
+        avg: Option<f64>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct HistogramBucket {
+    key: i128,
+    count: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct RangeBucket {
+    from: Option<i64>,
+    to: Option<i64>,
+    count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+
+    fn search(handler: &SearchHandler, request: &str) -> Response {
+        let request: Request = serde_json::from_str(request).unwrap();
+        let tokens = request.query.to_tokens().unwrap();
+        let limit = request.limit.unwrap();
+
+        handler
+            .text_index
+            .read(|reader, schema| {
+                handler.process_query(reader, schema, &tokens, &request, limit, 10)
+            })
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_not_pin_reader_without_next_page() {
+        let index = create_index(&[
+            &[("id", "1"), ("message", "connection refused")],
+            &[("id", "2"), ("message", "connection reset")],
+        ]);
+        let handler = SearchHandler::new(&index.config, &index.text_index);
+        let response = search(&handler, r#"{"query": "connection", "limit": 2}"#);
+
+        assert_eq!(response.hits.len(), 2);
+        assert_eq!(response.cursor.is_none(), true);
+        assert_eq!(handler.pinned_readers.count(), 0);
+    }
+
+    #[test]
+    fn should_read_next_page_from_pinned_reader() {
+        let mut index = create_index(&[
+            &[("id", "1"), ("message", "connection refused")],
+            &[("id", "2"), ("message", "connection reset")],
+        ]);
+        let handler = SearchHandler::new(&index.config, &index.text_index);
+        let response = search(&handler, r#"{"query": "connection", "limit": 1}"#);
+
+        assert_eq!(response.hits[0]["id"], Some("1".into()));
+        assert_eq!(handler.pinned_readers.count(), 1);
+
+        index.add_documents(&[&[("id", "3"), ("message", "connection")]]);
+
+        let request = format!(
+            r#"{{"query": "connection", "limit": 1, "cursor": "{}"}}"#,
+            response.cursor.unwrap()
+        );
+        let response = search(&handler, &request);
+
+        assert_eq!(response.hits.len(), 1);
+        assert_eq!(response.hits[0]["id"], Some("2".into()));
+        assert_eq!(response.cursor.is_none(), true);
+        assert_eq!(handler.pinned_readers.count(), 1);
+    }
 }
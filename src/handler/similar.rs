@@ -0,0 +1,298 @@
+use crate::collector::SortCollector;
+use crate::config::ConfigRef;
+use crate::config::DataType;
+use crate::handler::builder::QueryBuilder;
+use crate::handler::util::document_to_map;
+use crate::handler::util::find_document;
+use crate::handler::util::handle_request;
+use crate::handler::util::key_field;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::index::TextIndexRef;
+use crate::parser::Occurance;
+use crate::parser::Token;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use tantivy::collector::Count;
+use tantivy::schema::Field;
+use tantivy::schema::Value;
+use tantivy::Document;
+use tantivy::IndexReader;
+use tantivy::Searcher;
+use tantivy::Term;
+
+#[derive(Debug)]
+pub struct SimilarHandler {
+    config: ConfigRef,
+    text_index: TextIndexRef,
+    query_builder: QueryBuilder,
+}
+
+impl SimilarHandler {
+    pub fn new(config: &ConfigRef, text_index: &TextIndexRef) -> SimilarHandler {
+        SimilarHandler {
+            config: config.clone(),
+            text_index: text_index.clone(),
+            query_builder: QueryBuilder::new(config),
+        }
+    }
+
+    fn process_query(
+        &self,
+        reader: &IndexReader,
+        schema: &HashMap<String, Field>,
+        key: i64,
+        limit: usize,
+    ) -> HandlerResult<Response> {
+        let searcher = reader.searcher();
+        let index_schema = searcher.schema();
+        let key_field = key_field(&self.config, schema)?;
+        let address = find_document(&searcher, key_field, key)?
+            .ok_or_else(|| HandlerError::new(&format!("Document `{}` not found", key)))?;
+        let document = searcher
+            .doc(address)
+            .map_err(|err| HandlerError::new(&format!("Search document - {:?}", err)))?;
+        let terms = self.distinctive_terms(schema, &searcher, &document)?;
+
+        if terms.is_empty() {
+            return Ok(Response {
+                key,
+                total: 0,
+                limit,
+                terms,
+                hits: Vec::new(),
+            });
+        }
+
+        // The most distinctive term gets boost 1, weights of other terms are relative to it.
+        let max_weight = terms[0].weight;
+        let mut tokens: Vec<_> = terms
+            .iter()
+            .map(|term| Token::Boost {
+                token: Box::new(Token::FilterEquals {
+                    occurance: None,
+                    field: term.field.clone(),
+                    value: term.value.clone(),
+                }),
+                boost: (term.weight / max_weight).to_string(),
+            })
+            .collect();
+
+        // Key field is always indexed, so source document is excluded by its key.
+        if let Some(field_config) = self.config.key_field() {
+            tokens.push(Token::FilterEquals {
+                occurance: Some(Occurance::MustNot),
+                field: field_config.name().into(),
+                value: key.to_string(),
+            });
+        }
+
+        let query = self.query_builder.build_query(&tokens, schema, &searcher)?;
+        let collector = SortCollector::new(Vec::new(), key_field, limit);
+        let (total, sorted_docs) = searcher
+            .search(&query, &(Count, collector))
+            .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+        let mut hits = Vec::new();
+
+        for sorted_doc in &sorted_docs {
+            let retrieved_doc = searcher
+                .doc(sorted_doc.address())
+                .map_err(|err| HandlerError::new(&format!("Search document - {:?}", err)))?;
+
+            hits.push(document_to_map(index_schema, &retrieved_doc));
+        }
+
+        Ok(Response {
+            key,
+            total,
+            limit,
+            terms,
+            hits,
+        })
+    }
+
+    // Terms of stored text fields ordered by tf-idf, terms which occur only in source document are skipped.
+    fn distinctive_terms(
+        &self,
+        schema: &HashMap<String, Field>,
+        searcher: &Searcher,
+        document: &Document,
+    ) -> HandlerResult<Vec<SimilarTerm>> {
+        let num_docs = searcher.num_docs() as f32;
+        let mut result = Vec::new();
+
+        for field_config in self.config.schema() {
+            match field_config.data_type() {
+                DataType::Text => {}
+                _ => continue,
+            }
+
+            let field = schema.get(field_config.name()).cloned().ok_or_else(|| {
+                HandlerError::new(&format!("Field `{}` not defined", field_config.name()))
+            })?;
+            let tokenizer = searcher
+                .index()
+                .tokenizer_for_field(field)
+                .map_err(|err| HandlerError::new(&format!("Tokenizer error - {:?}", err)))?;
+            let mut frequencies: HashMap<String, u32> = HashMap::new();
+
+            for value in document.get_all(field) {
+                if let Value::Str(text) = value {
+                    tokenizer.token_stream(text).process(&mut |token| {
+                        *frequencies.entry(token.text.clone()).or_insert(0) += 1;
+                    });
+                }
+            }
+
+            for (value, frequency) in frequencies {
+                let doc_freq = searcher.doc_freq(&Term::from_field_text(field, &value));
+
+                if doc_freq > 1 {
+                    result.push(SimilarTerm {
+                        field: field_config.name().into(),
+                        value,
+                        weight: frequency as f32 * (num_docs / doc_freq as f32).ln(),
+                    });
+                }
+            }
+        }
+
+        result.sort_by(|left, right| {
+            right
+                .weight
+                .partial_cmp(&left.weight)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.field.cmp(&right.field))
+                .then_with(|| left.value.cmp(&right.value))
+        });
+        result.retain(|term| term.weight > 0.0);
+        result.truncate(self.config.search().similar_terms());
+
+        Ok(result)
+    }
+}
+
+impl Handler for SimilarHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        handle_request(request, move |request: Request| {
+            let search_config = self.config.search();
+            let limit = request
+                .limit
+                .unwrap_or_else(|| search_config.default_limit());
+
+            if limit == 0 || limit > search_config.max_limit() {
+                return Err(HandlerError::new(&format!(
+                    "Limit must be from 1 to {}, but {} given",
+                    search_config.max_limit(),
+                    limit
+                )));
+            }
+
+            self.text_index
+                .read(|reader, schema| self.process_query(reader, schema, request.key, limit))
+                .map_err(|err| HandlerError::new(&format!("{}", err)))?
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    key: i64,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    key: i64,
+    total: usize,
+    limit: usize,
+    terms: Vec<SimilarTerm>,
+    hits: Vec<HashMap<String, Option<String>>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SimilarTerm {
+    field: String,
+    value: String,
+    weight: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+    use crate::index::testing::TestIndex;
+
+    fn create_test_index() -> TestIndex {
+        create_index(&[
+            &[
+                ("id", "1"),
+                ("level", "error"),
+                ("message", "log connection refused connection unique"),
+            ],
+            &[("id", "2"), ("message", "log connection timeout")],
+            &[
+                ("id", "3"),
+                ("level", "error"),
+                ("message", "log refused disk"),
+            ],
+            &[("id", "4"), ("message", "log disk full")],
+            &[("id", "5"), ("message", "log other")],
+        ])
+    }
+
+    fn similar(index: &TestIndex, key: i64) -> Response {
+        let handler = SimilarHandler::new(&index.config, &index.text_index);
+
+        index
+            .text_index
+            .read(|reader, schema| handler.process_query(reader, schema, key, 10))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_weight_terms_by_frequency_and_rarity() {
+        let response = similar(&create_test_index(), 1);
+        let terms: Vec<_> = response
+            .terms
+            .iter()
+            .map(|term| (term.field.as_str(), term.value.as_str(), term.weight))
+            .collect();
+
+        // Term of every document has zero weight and term of only source document is skipped.
+        assert_eq!(
+            terms,
+            vec![
+                ("message", "connection", 2.0 * (5.0f32 / 2.0).ln()),
+                ("level", "error", (5.0f32 / 2.0).ln()),
+                ("message", "refused", (5.0f32 / 2.0).ln()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_find_similar_documents_without_source() {
+        let response = similar(&create_test_index(), 1);
+        let keys: Vec<_> = response
+            .hits
+            .iter()
+            .map(|hit| hit["id"].clone().unwrap())
+            .collect();
+
+        assert_eq!(response.total, 2);
+        assert_eq!(keys, vec!["2", "3"]);
+    }
+
+    #[test]
+    fn should_not_find_similar_documents_without_shared_terms() {
+        let response = similar(&create_test_index(), 5);
+
+        assert_eq!(response.terms.is_empty(), true);
+        assert_eq!(response.total, 0);
+    }
+}
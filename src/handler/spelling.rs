@@ -0,0 +1,243 @@
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::parser::parse_query_with_positions;
+use crate::parser::Occurance;
+use crate::parser::Token;
+use levenshtein_automata::LevenshteinAutomatonBuilder;
+use std::collections::HashMap;
+use std::vec::IntoIter;
+use tantivy::schema::Field;
+use tantivy::Searcher;
+use tantivy::Term;
+
+const MAX_DISTANCE: u8 = 2;
+// Short words are close to too many index terms to be corrected or to be used as correction.
+const MIN_WORD_LENGTH: usize = 3;
+
+// Replaces unknown words of text terms in query string with the most frequent index terms within edit distance.
+pub fn correct_query(
+    query: &str,
+    searcher: &Searcher,
+    fields: &[Field],
+) -> HandlerResult<Option<String>> {
+    let (tokens, positions) =
+        parse_query_with_positions(query).map_err(|err| HandlerError::new(&format!("{}", err)))?;
+    let speller = Speller {
+        searcher,
+        fields,
+        automaton_builder: LevenshteinAutomatonBuilder::new(MAX_DISTANCE, true),
+    };
+
+    Ok(rewrite_query(query, &tokens, positions, |word| {
+        speller.correct_word(word)
+    }))
+}
+
+// Replaces words of text terms in query string by given corrections, other parts of query are kept as written.
+fn rewrite_query<F>(
+    query: &str,
+    tokens: &[Token],
+    positions: Vec<usize>,
+    correct: F,
+) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut rewriter = Rewriter {
+        correct,
+        chars: query.chars().collect(),
+        positions: positions.into_iter(),
+        corrections: Vec::new(),
+    };
+
+    rewriter.check_tokens(tokens);
+
+    if rewriter.corrections.is_empty() {
+        return None;
+    }
+
+    let mut chars = rewriter.chars;
+
+    // Corrections are applied from the end, so positions of previous words are not shifted.
+    for (start, length, correction) in rewriter.corrections.into_iter().rev() {
+        chars.splice(start..start + length, correction.chars());
+    }
+
+    Some(chars.into_iter().collect())
+}
+
+struct Rewriter<F> {
+    correct: F,
+    chars: Vec<char>,
+    positions: IntoIter<usize>,
+    corrections: Vec<(usize, usize, String)>,
+}
+
+impl<F> Rewriter<F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn check_tokens(&mut self, tokens: &[Token]) {
+        for token in tokens {
+            self.check_token(token);
+        }
+    }
+
+    fn check_token(&mut self, token: &Token) {
+        match token {
+            Token::Group { tokens, .. } => return self.check_tokens(tokens),
+            Token::Boost { token, .. } => return self.check_token(token),
+            _ => {}
+        }
+
+        // Positions of terms are consumed in the same order as tokens are validated by parse handler.
+        let position = self.positions.next();
+
+        if let (Token::Text { occurance, strings }, Some(mut position)) = (token, position) {
+            // Excluded words do not prevent documents from matching.
+            if occurance == &Some(Occurance::MustNot) {
+                return;
+            }
+
+            for string in strings {
+                let start = match self.find(string, position) {
+                    Some(start) => start,
+                    None => break,
+                };
+                let length = string.chars().count();
+
+                position = start + length;
+
+                if let Some(correction) = (self.correct)(string) {
+                    self.corrections.push((start, length, correction));
+                }
+            }
+        }
+    }
+
+    // Words of text term are searched in query string after the start of term.
+    fn find(&self, string: &str, position: usize) -> Option<usize> {
+        let word: Vec<_> = string.chars().collect();
+
+        (position..self.chars.len()).find(|&start| self.chars[start..].starts_with(&word))
+    }
+}
+
+struct Speller<'a> {
+    searcher: &'a Searcher,
+    fields: &'a [Field],
+    automaton_builder: LevenshteinAutomatonBuilder,
+}
+
+impl<'a> Speller<'a> {
+    fn correct_word(&self, string: &str) -> Option<String> {
+        let word = string.to_lowercase();
+
+        if word.chars().count() < MIN_WORD_LENGTH || !word.chars().all(char::is_alphanumeric) {
+            return None;
+        }
+
+        let known = self
+            .fields
+            .iter()
+            .any(|&field| self.searcher.doc_freq(&Term::from_field_text(field, &word)) > 0);
+
+        if known {
+            return None;
+        }
+
+        let automaton = self.automaton_builder.build_dfa(&word);
+        let mut counts: HashMap<String, u64> = HashMap::new();
+
+        for segment_reader in self.searcher.segment_readers() {
+            for &field in self.fields {
+                let inverted_index = segment_reader.inverted_index(field);
+                let mut stream = inverted_index.terms().search(&automaton).into_stream();
+
+                while stream.advance() {
+                    let value = String::from_utf8_lossy(stream.key()).into_owned();
+
+                    if value.chars().count() < MIN_WORD_LENGTH {
+                        continue;
+                    }
+
+                    *counts.entry(value).or_insert(0) += u64::from(stream.value().doc_freq);
+                }
+            }
+        }
+
+        counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+            .map(|(value, _)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(query: &str) -> Option<String> {
+        let (tokens, positions) = parse_query_with_positions(query).unwrap();
+
+        rewrite_query(query, &tokens, positions, |word| match word {
+            "conection" => Some("connection".into()),
+            "eror" => Some("error".into()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn should_rewrite_text_terms() {
+        assert_eq!(rewrite("conection eror"), Some("connection error".into()));
+        assert_eq!(
+            rewrite("+conection timeout"),
+            Some("+connection timeout".into())
+        );
+    }
+
+    #[test]
+    fn should_not_rewrite_known_terms() {
+        assert_eq!(rewrite("connection timeout"), None);
+    }
+
+    #[test]
+    fn should_rewrite_grouped_terms() {
+        assert_eq!(
+            rewrite("(conection OR eror) AND timeout"),
+            Some("(connection OR error) AND timeout".into())
+        );
+    }
+
+    #[test]
+    fn should_rewrite_boosted_terms() {
+        assert_eq!(
+            rewrite("conection^2 (eror timeout)^0.5"),
+            Some("connection^2 (error timeout)^0.5".into())
+        );
+    }
+
+    #[test]
+    fn should_rewrite_escaped_terms() {
+        assert_eq!(
+            rewrite(r"host\:conection \-eror"),
+            Some(r"host\:conection \-error".into())
+        );
+    }
+
+    #[test]
+    fn should_rewrite_joined_terms() {
+        assert_eq!(
+            rewrite("conection_eror_timeout"),
+            Some("connection_error_timeout".into())
+        );
+    }
+
+    #[test]
+    fn should_not_rewrite_excluded_terms_and_filters() {
+        assert_eq!(
+            rewrite("-conection message:eror \"eror\" eror"),
+            Some("-conection message:eror \"eror\" error".into())
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::config::ConfigRef;
+use crate::config::DataType;
+use crate::handler::builder::QueryBuilder;
+use crate::handler::util::handle_empty;
+use crate::handler::HandlerError;
+use crate::handler::HandlerResult;
+use crate::index::TextIndexRef;
+use iron::middleware::Handler;
+use iron::IronResult;
+use iron::Request as IronRequest;
+use iron::Response as IronResponse;
+use std::collections::HashMap;
+use tantivy::schema::Field;
+use tantivy::IndexReader;
+
+#[derive(Debug)]
+pub struct SuggestHandler {
+    config: ConfigRef,
+    text_index: TextIndexRef,
+    query_builder: QueryBuilder,
+}
+
+impl SuggestHandler {
+    pub fn new(config: &ConfigRef, text_index: &TextIndexRef) -> SuggestHandler {
+        SuggestHandler {
+            config: config.clone(),
+            text_index: text_index.clone(),
+            query_builder: QueryBuilder::new(config),
+        }
+    }
+
+    fn process_query(
+        &self,
+        reader: &IndexReader,
+        schema: &HashMap<String, Field>,
+        prefix: &str,
+        field: Option<&str>,
+    ) -> HandlerResult<Response> {
+        let (field, prefix) = split_prefix(prefix, field);
+        // Prefix without field can also be the beginning of field name.
+        let fields = match field {
+            Some(_) => Vec::new(),
+            None => self.field_names(prefix),
+        };
+        let text_fields = self.text_fields(schema, field)?;
+
+        Ok(Response {
+            fields,
+            terms: self.terms(reader, &text_fields, &prefix.to_lowercase()),
+        })
+    }
+
+    fn field_names(&self, prefix: &str) -> Vec<String> {
+        self.config
+            .schema()
+            .iter()
+            .map(|field| field.name())
+            .filter(|name| name.starts_with(prefix))
+            // Terms of not indexed fields can not be searched, so they are not suggested.
+            .filter(|name| match self.query_builder.field_kind(name) {
+                Ok((_, indexed)) => indexed,
+                Err(_) => false,
+            })
+            .map(|name| format!("{}:", name))
+            .collect()
+    }
+
+    // Numeric fields do not have suggestions, because their terms are not readable.
+    fn text_fields(
+        &self,
+        schema: &HashMap<String, Field>,
+        name: Option<&str>,
+    ) -> HandlerResult<Vec<Field>> {
+        let mut result = Vec::new();
+
+        if let Some(name) = name {
+            if !self
+                .config
+                .schema()
+                .iter()
+                .any(|field| field.name() == name)
+            {
+                return Err(HandlerError::new(&format!("Field `{}` not defined", name)));
+            }
+        }
+
+        for field_config in self.config.schema() {
+            match name {
+                Some(name) if name != field_config.name() => continue,
+                _ => {}
+            }
+
+            if let DataType::Text = field_config.data_type() {
+                let field = schema.get(field_config.name()).cloned().ok_or_else(|| {
+                    HandlerError::new(&format!("Field `{}` not defined", field_config.name()))
+                })?;
+
+                result.push(field);
+            }
+        }
+
+        Ok(result)
+    }
+
+    // Document frequencies of every term are summed over all fields and segments.
+    fn terms(&self, reader: &IndexReader, fields: &[Field], prefix: &str) -> Vec<TermValue> {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+
+        for segment_reader in reader.searcher().segment_readers() {
+            for &field in fields {
+                let inverted_index = segment_reader.inverted_index(field);
+                let mut stream = inverted_index.terms().range().ge(prefix).into_stream();
+
+                while stream.advance() {
+                    if !stream.key().starts_with(prefix.as_bytes()) {
+                        break;
+                    }
+
+                    let value = String::from_utf8_lossy(stream.key()).into_owned();
+
+                    *counts.entry(value).or_insert(0) += u64::from(stream.value().doc_freq);
+                }
+            }
+        }
+
+        let mut values: Vec<_> = counts
+            .into_iter()
+            .map(|(value, count)| TermValue { value, count })
+            .collect();
+
+        values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        values.truncate(self.config.search().suggest_size());
+        values
+    }
+}
+
+impl Handler for SuggestHandler {
+    fn handle(&self, request: &mut IronRequest) -> IronResult<IronResponse> {
+        let mut prefix = String::new();
+        let mut field = None;
+
+        for (name, value) in request.url.as_ref().query_pairs() {
+            match name.as_ref() {
+                "prefix" => prefix = value.into_owned(),
+                "field" if !value.is_empty() => field = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+
+        handle_empty(move || {
+            self.text_index
+                .read(|reader, schema| {
+                    self.process_query(reader, schema, &prefix, field.as_deref())
+                })
+                .map_err(|err| HandlerError::new(&format!("{}", err)))?
+        })
+    }
+}
+
+// Field given in prefix takes precedence over field parameter.
+fn split_prefix<'a>(prefix: &'a str, field: Option<&'a str>) -> (Option<&'a str>, &'a str) {
+    match prefix.find(':') {
+        Some(position) => (Some(&prefix[..position]), &prefix[position + 1..]),
+        None => (field, prefix),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    fields: Vec<String>,
+    terms: Vec<TermValue>,
+}
+
+#[derive(Debug, Serialize)]
+struct TermValue {
+    value: String,
+    count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::testing::create_index;
+
+    fn suggest(prefix: &str, field: Option<&str>) -> HandlerResult<(Vec<String>, Vec<String>)> {
+        let index = create_index(&[
+            &[
+                ("id", "1"),
+                ("level", "error"),
+                ("message", "connection refused"),
+            ],
+            &[
+                ("id", "2"),
+                ("level", "info"),
+                ("message", "connection reset"),
+            ],
+            &[("id", "3"), ("level", "error"), ("message", "error report")],
+        ]);
+        let handler = SuggestHandler::new(&index.config, &index.text_index);
+
+        index
+            .text_index
+            .read(|reader, schema| handler.process_query(reader, schema, prefix, field))
+            .unwrap()
+            .map(|response| {
+                let terms = response
+                    .terms
+                    .into_iter()
+                    .map(|term| format!("{}={}", term.value, term.count))
+                    .collect();
+
+                (response.fields, terms)
+            })
+    }
+
+    #[test]
+    fn should_split_prefix() {
+        assert_eq!(split_prefix("err", None), (None, "err"));
+        assert_eq!(split_prefix("err", Some("level")), (Some("level"), "err"));
+        assert_eq!(split_prefix("level:err", None), (Some("level"), "err"));
+        assert_eq!(split_prefix("level:", Some("message")), (Some("level"), ""));
+        assert_eq!(split_prefix("a:b:c", None), (Some("a"), "b:c"));
+    }
+
+    #[test]
+    fn should_suggest_terms_of_all_text_fields() {
+        let (fields, terms) = suggest("Re", None).unwrap();
+
+        assert_eq!(fields.is_empty(), true);
+        assert_eq!(terms, vec!["refused=1", "report=1", "reset=1"]);
+        assert_eq!(suggest("err", None).unwrap().1, vec!["error=3"]);
+    }
+
+    #[test]
+    fn should_suggest_terms_of_field() {
+        assert_eq!(suggest("level:err", None).unwrap().1, vec!["error=2"]);
+        assert_eq!(suggest("err", Some("message")).unwrap().1, vec!["error=1"]);
+        assert_eq!(suggest("level:", None).unwrap().1.is_empty(), true);
+        assert_eq!(suggest("other:err", None).is_err(), true);
+    }
+
+    #[test]
+    fn should_suggest_only_indexed_fields() {
+        assert_eq!(
+            suggest("", None).unwrap().0,
+            vec!["id:", "size:", "level:", "message:"]
+        );
+        assert_eq!(suggest("re", None).unwrap().0.is_empty(), true);
+        assert_eq!(suggest("le", None).unwrap().0, vec!["level:"]);
+    }
+}
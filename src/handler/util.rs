@@ -1,15 +1,31 @@
+use crate::config::Config;
+use crate::handler::HandlerError;
 use crate::handler::HandlerResult;
+use crate::parser::Occurance;
+use crate::parser::Token;
 use iron::mime::Mime;
 use iron::mime::SubLevel;
 use iron::mime::TopLevel;
 use iron::status;
+use iron::status::Status;
 use iron::IronResult;
 use iron::Request;
 use iron::Response;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
+use std::collections::HashMap;
 use std::io::Read;
+use tantivy::collector::TopDocs;
+use tantivy::query::TermQuery;
+use tantivy::schema::Field;
+use tantivy::schema::IndexRecordOption;
+use tantivy::schema::Schema;
+use tantivy::schema::Value;
+use tantivy::DocAddress;
+use tantivy::Document;
+use tantivy::Searcher;
+use tantivy::Term;
 
 pub fn handle_empty<Res, F>(callback: F) -> IronResult<Response>
 where
@@ -21,7 +37,22 @@ where
         Err(err) => ErrorResponse::error(&format!("{}", err)),
     };
 
-    struct_to_response(&response)
+    struct_to_response(status::Ok, &response)
+}
+
+// Returns 404 status with JSON error when callback does not find requested object.
+pub fn handle_found<Res, F>(not_found: &str, callback: F) -> IronResult<Response>
+where
+    Res: Serialize,
+    F: FnOnce() -> HandlerResult<Option<Res>>,
+{
+    match callback() {
+        Ok(Some(response)) => struct_to_response(status::Ok, &ErrorResponse::success(response)),
+        Ok(None) => struct_to_response(status::NotFound, &ErrorResponse::<()>::error(not_found)),
+        Err(err) => {
+            struct_to_response(status::Ok, &ErrorResponse::<()>::error(&format!("{}", err)))
+        }
+    }
 }
 
 pub fn handle_request<Req, Res, F>(request: &mut Request, callback: F) -> IronResult<Response>
@@ -39,10 +70,89 @@ where
         Err(err) => ErrorResponse::error(&format!("{}", err)),
     };
 
-    struct_to_response(&response)
+    struct_to_response(status::Ok, &response)
+}
+
+// Returns JSON error response for handlers which stream their results.
+pub fn handle_stream<F>(callback: F) -> IronResult<Response>
+where
+    F: FnOnce() -> HandlerResult<Response>,
+{
+    match callback() {
+        Ok(response) => Ok(response),
+        Err(err) => {
+            struct_to_response(status::Ok, &ErrorResponse::<()>::error(&format!("{}", err)))
+        }
+    }
+}
+
+pub fn key_field(config: &Config, schema: &HashMap<String, Field>) -> HandlerResult<Field> {
+    config
+        .key_field()
+        .and_then(|field| schema.get(field.name()).cloned())
+        .ok_or_else(|| HandlerError::new("Key field not defined"))
+}
+
+pub fn find_document(
+    searcher: &Searcher,
+    key_field: Field,
+    key: i64,
+) -> HandlerResult<Option<DocAddress>> {
+    let query = TermQuery::new(
+        Term::from_field_i64(key_field, key),
+        IndexRecordOption::Basic,
+    );
+    let top_docs = searcher
+        .search(&query, &TopDocs::with_limit(1))
+        .map_err(|err| HandlerError::new(&format!("Search error - {:?}", err)))?;
+
+    Ok(top_docs.first().map(|&(_, address)| address))
+}
+
+// Converts stored values of document, hidden fields are skipped.
+pub fn document_to_map(schema: &Schema, document: &Document) -> HashMap<String, Option<String>> {
+    let mut result = HashMap::new();
+
+    for field_value in document.field_values() {
+        let name = schema.get_field_name(field_value.field());
+
+        // Hidden facet fields are stored by tantivy, but they only repeat other values.
+        if name.starts_with("__") {
+            continue;
+        }
+
+        result.insert(name.into(), value_to_string(field_value.value()));
+    }
+
+    result
+}
+
+// Filter is required as a whole, but query of only excluded clauses matches nothing inside of a group.
+pub fn filter_tokens(filter: &[Token]) -> Vec<Token> {
+    let only_excluded = filter
+        .iter()
+        .all(|token| token.occurance() == &Some(Occurance::MustNot));
+
+    if only_excluded {
+        filter.to_vec()
+    } else {
+        vec![Token::Group {
+            occurance: Some(Occurance::Must),
+            tokens: filter.to_vec(),
+        }]
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Str(value) => Some(value.clone()),
+        Value::U64(value) => Some(value.to_string()),
+        Value::I64(value) => Some(value.to_string()),
+        _ => None,
+    }
 }
 
-fn struct_to_response<Res>(value: &Res) -> IronResult<Response>
+fn struct_to_response<Res>(status: Status, value: &Res) -> IronResult<Response>
 where
     Res: Serialize,
 {
@@ -50,7 +160,7 @@ where
         Ok(body) => {
             let content_type = Mime(TopLevel::Application, SubLevel::Json, vec![]);
 
-            Ok(Response::with((status::Ok, content_type, body)))
+            Ok(Response::with((status, content_type, body)))
         }
         Err(_) => Ok(Response::with((status::InternalServerError,))),
     }
@@ -1,4 +1,6 @@
 mod error;
+#[cfg(test)]
+pub mod testing;
 
 pub use self::error::TextIndexError;
 pub use self::error::TextIndexResult;
@@ -12,16 +14,22 @@ use std::fmt::Result as FmtResult;
 use std::sync::Arc;
 use std::sync::Mutex;
 use tantivy::directory::MmapDirectory;
+use tantivy::schema::Cardinality;
 use tantivy::schema::Field;
+use tantivy::schema::IndexRecordOption;
+use tantivy::schema::IntOptions;
 use tantivy::schema::SchemaBuilder;
-use tantivy::schema::INDEXED;
-use tantivy::schema::STORED;
-use tantivy::schema::TEXT;
+use tantivy::schema::TextFieldIndexing;
+use tantivy::schema::TextOptions;
+use tantivy::schema::STRING;
 use tantivy::Index;
 use tantivy::IndexReader;
 use tantivy::IndexWriter;
 use tantivy::ReloadPolicy;
 
+pub const PRESENT_FIELD: &str = "__present";
+const FACET_FIELD_PREFIX: &str = "__facet_";
+
 #[derive(Debug, Clone)]
 pub struct TextIndexRef {
     inner: Arc<Mutex<TextIndex>>,
@@ -73,21 +81,33 @@ impl TextIndex {
 
         for field_config in config.schema() {
             let name = field_config.name().to_string();
+            let is_key = field_config.column() == config.datasource().key();
             let field = match field_config.data_type() {
-                DataType::Int { indexed: true } => {
-                    schema_builder.add_i64_field(&name, INDEXED | STORED)
-                }
-                DataType::Int { indexed: false } => schema_builder.add_i64_field(&name, STORED),
-                DataType::UInt { indexed: true } => {
-                    schema_builder.add_u64_field(&name, INDEXED | STORED)
+                // Key values are used to find documents and to order documents with equal relevance.
+                DataType::Int { indexed, sortable } => schema_builder
+                    .add_i64_field(&name, int_options(indexed || is_key, sortable || is_key)),
+                DataType::UInt { indexed, sortable } => {
+                    schema_builder.add_u64_field(&name, int_options(indexed, sortable))
                 }
-                DataType::UInt { indexed: false } => schema_builder.add_u64_field(&name, STORED),
-                DataType::Text => schema_builder.add_text_field(&name, TEXT | STORED),
+                DataType::Text => schema_builder.add_text_field(&name, text_options()),
             };
 
+            // Values of facet fields are also indexed as is in hidden field to count them.
+            if field_config.facet() {
+                let facet_name = facet_field_name(&name);
+                let facet_field = schema_builder.add_facet_field(&facet_name);
+
+                fields.insert(facet_name, facet_field);
+            }
+
             fields.insert(name, field);
         }
 
+        // Hidden field contains names of all non null fields of document.
+        let present_field = schema_builder.add_text_field(PRESENT_FIELD, STRING);
+
+        fields.insert(PRESENT_FIELD.into(), present_field);
+
         let schema = schema_builder.build();
         let directory = MmapDirectory::open(config.index_path())
             .map_err(TextIndexError::open_directory_error)?;
@@ -144,6 +164,36 @@ impl TextIndex {
     }
 }
 
+pub fn facet_field_name(name: &str) -> String {
+    format!("{}{}", FACET_FIELD_PREFIX, name)
+}
+
+// Phrase queries require term positions, so they are always recorded for text fields.
+fn text_options() -> TextOptions {
+    let indexing = TextFieldIndexing::default()
+        .set_tokenizer("default")
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+
+    TextOptions::default()
+        .set_indexing_options(indexing)
+        .set_stored()
+}
+
+// Sortable fields are stored as fast fields to read their values during search.
+fn int_options(indexed: bool, sortable: bool) -> IntOptions {
+    let mut options = IntOptions::default().set_stored();
+
+    if indexed {
+        options = options.set_indexed();
+    }
+
+    if sortable {
+        options = options.set_fast(Cardinality::SingleValue);
+    }
+
+    options
+}
+
 impl Debug for TextIndex {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         write!(f, "TextIndex {{ fields: {:?}, ... }}", self.fields)
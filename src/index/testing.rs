@@ -0,0 +1,92 @@
+use super::facet_field_name;
+use super::TextIndexRef;
+use super::PRESENT_FIELD;
+use crate::config::Config;
+use crate::config::ConfigRef;
+use crate::config::DataType;
+use std::sync::Arc;
+use tantivy::schema::Facet;
+use tantivy::Document;
+use tempdir::TempDir;
+
+const CONFIG: &str = r#"
+state_file: state.yaml
+interval: 10
+datasource: {host: localhost, port: 5432, database: logs, user: logs, password: logs, key: id, query: ""}
+schema:
+  - {name: id, column: id, display: Id, description: Key, data_type: {type: Int, indexed: true, sortable: true}}
+  - {name: size, column: size, display: Size, description: Size, data_type: {type: Int, indexed: true, sortable: true}}
+  - {name: rev, column: rev, display: Rev, description: Revision, data_type: {type: UInt, indexed: false, sortable: false}}
+  - {name: level, column: level, display: Level, description: Level, facet: true, data_type: {type: Text}}
+  - {name: message, column: message, display: Message, description: Message, data_type: {type: Text}}
+"#;
+
+// Index lives in temporary directory, which is removed when index is dropped.
+pub struct TestIndex {
+    pub config: ConfigRef,
+    pub text_index: TextIndexRef,
+    _directory: TempDir,
+}
+
+impl TestIndex {
+    // Documents are written in one commit as worker writes them, missing fields are null.
+    pub fn add_documents(&mut self, documents: &[&[(&str, &str)]]) {
+        let config = self.config.clone();
+
+        self.text_index
+            .write(|writer, schema| -> Result<(), ()> {
+                for values in documents {
+                    let mut document = Document::new();
+
+                    for &(name, value) in values.iter() {
+                        let field_config = config
+                            .schema()
+                            .iter()
+                            .find(|field| field.name() == name)
+                            .unwrap();
+                        let field = schema[name];
+
+                        match field_config.data_type() {
+                            DataType::Int { .. } => document.add_i64(field, value.parse().unwrap()),
+                            DataType::UInt { .. } => {
+                                document.add_u64(field, value.parse().unwrap())
+                            }
+                            DataType::Text => document.add_text(field, value),
+                        }
+
+                        document.add_text(schema[PRESENT_FIELD], name);
+
+                        if field_config.facet() {
+                            let facet_field = schema[&facet_field_name(name)];
+
+                            document.add_facet(facet_field, Facet::from_path(vec![value]));
+                        }
+                    }
+
+                    writer.add_document(document);
+                }
+
+                Ok(())
+            })
+            .unwrap()
+            .unwrap();
+        self.text_index
+            .read(|reader, _| reader.reload())
+            .unwrap()
+            .unwrap();
+    }
+}
+
+pub fn create_index(documents: &[&[(&str, &str)]]) -> TestIndex {
+    let directory = TempDir::new("index").unwrap();
+    let config = format!("index_path: {:?}\n{}", directory.path(), CONFIG);
+    let config: ConfigRef = Arc::new(serde_yaml::from_str::<Config>(&config).unwrap());
+    let mut index = TestIndex {
+        text_index: TextIndexRef::new(&config, true).unwrap(),
+        config,
+        _directory: directory,
+    };
+
+    index.add_documents(documents);
+    index
+}
@@ -3,12 +3,14 @@ extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
+mod collector;
 mod config;
 mod error;
 mod handler;
 mod index;
 mod options;
 mod parser;
+mod query;
 mod server;
 mod worker;
 
@@ -0,0 +1,145 @@
+#[derive(Debug)]
+pub struct Cursor {
+    chars: Vec<char>,
+    position: usize,
+    terms: Vec<usize>,
+}
+
+impl Cursor {
+    pub fn new(query: &str) -> Cursor {
+        Cursor {
+            chars: query.chars().collect(),
+            position: 0,
+            terms: Vec::new(),
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Remembers current position as start of the next term.
+    pub fn mark_term(&mut self) {
+        self.terms.push(self.position);
+    }
+
+    pub fn terms(&self) -> &[usize] {
+        &self.terms
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    pub fn next_char(&mut self) -> Option<char> {
+        let result = self.peek();
+
+        if result.is_some() {
+            self.position += 1;
+        }
+
+        result
+    }
+
+    pub fn consume(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn consume_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+
+        let end = self.position + keyword.chars().count();
+        let matches = end <= self.chars.len()
+            && self.chars[self.position..end]
+                .iter()
+                .cloned()
+                .eq(keyword.chars());
+        let separated = match self.chars.get(end) {
+            Some(&c) => c.is_whitespace() || c == '(',
+            None => true,
+        };
+
+        if matches && separated {
+            self.position = end;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    pub fn read_while<P>(&mut self, predicate: P) -> String
+    where
+        P: Fn(char) -> bool,
+    {
+        let mut result = String::new();
+
+        while let Some(c) = self.peek() {
+            if !predicate(c) {
+                break;
+            }
+
+            result.push(c);
+            self.position += 1;
+        }
+
+        result
+    }
+
+    // Backslash escapes any character, second value is true if escaped characters were found.
+    pub fn read_escaped<P>(&mut self, predicate: P) -> (String, bool)
+    where
+        P: Fn(char) -> bool,
+    {
+        let mut result = String::new();
+        let mut escaped = false;
+
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                self.position += 1;
+                escaped = true;
+                result.push(self.next_char().unwrap_or('\\'));
+            } else if predicate(c) {
+                break;
+            } else {
+                result.push(c);
+                self.position += 1;
+            }
+        }
+
+        (result, escaped)
+    }
+
+    // Only quote and backslash can be escaped inside quotes, other backslashes are kept as is.
+    pub fn read_quoted(&mut self, quote: char) -> Option<String> {
+        let mut result = String::new();
+
+        loop {
+            match self.next_char()? {
+                c if c == quote => break,
+                '\\' => match self.peek() {
+                    Some(c) if c == quote || c == '\\' => {
+                        result.push(c);
+                        self.position += 1;
+                    }
+                    _ => result.push('\\'),
+                },
+                c => result.push(c),
+            }
+        }
+
+        Some(result)
+    }
+}
@@ -8,24 +8,54 @@ pub type QueryParserResult<T> = Result<T, QueryParserError>;
 #[derive(Debug)]
 pub struct QueryParserError {
     message: String,
+    position: usize,
+    query: Option<String>,
 }
 
 impl QueryParserError {
     #[allow(clippy::needless_pass_by_value)]
-    pub fn new<S>(message: S) -> QueryParserError
+    pub fn new<S>(position: usize, message: S) -> QueryParserError
     where
         S: AsRef<str>,
     {
         QueryParserError {
             message: message.as_ref().into(),
+            position,
+            query: None,
         }
     }
+
+    pub fn with_query(self, query: &str) -> QueryParserError {
+        QueryParserError {
+            query: Some(query.into()),
+            ..self
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
 }
 
 impl Error for QueryParserError {}
 
 impl Display for QueryParserError {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        write!(f, "{}", self.message)
+        write!(f, "{} at position {}", self.message, self.position)?;
+
+        if let Some(query) = &self.query {
+            let query: String = query
+                .chars()
+                .map(|c| if c.is_whitespace() { ' ' } else { c })
+                .collect();
+
+            write!(f, "\n{}\n{}^", query, " ".repeat(self.position))?;
+        }
+
+        Ok(())
     }
 }
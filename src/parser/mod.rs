@@ -1,15 +1,36 @@
+mod cursor;
 mod error;
+mod structured;
 
 pub use self::error::QueryParserError;
 pub use self::error::QueryParserResult;
+pub use self::structured::StructuredQuery;
 
-#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone)]
+use self::cursor::Cursor;
+use regex_syntax::hir::Hir;
+use regex_syntax::hir::HirKind;
+use regex_syntax::hir::Literal;
+use regex_syntax::hir::RepetitionKind;
+use regex_syntax::hir::RepetitionRange;
+use regex_syntax::Parser as RegexParser;
+use tantivy_fst::Regex;
+
+const MAX_FUZZY_DISTANCE: u8 = 2;
+
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Serialize)]
 pub enum Occurance {
     Must,
     MustNot,
 }
 
-#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Serialize)]
+pub enum RangeBound {
+    Included(String),
+    Excluded(String),
+    Unbounded,
+}
+
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Serialize)]
 pub enum Token {
     Text {
         occurance: Option<Occurance>,
@@ -20,55 +41,546 @@ pub enum Token {
         field: String,
         value: String,
     },
+    FilterExists {
+        occurance: Option<Occurance>,
+        field: String,
+        exists: bool,
+    },
+    FilterIn {
+        occurance: Option<Occurance>,
+        field: String,
+        values: Vec<String>,
+    },
     FilterRange {
         occurance: Option<Occurance>,
         field: String,
-        left_bound: Option<String>,
-        right_bound: Option<String>,
+        left_bound: RangeBound,
+        right_bound: RangeBound,
+    },
+    Phrase {
+        occurance: Option<Occurance>,
+        field: Option<String>,
+        strings: Vec<String>,
+        slop: u32,
+    },
+    Group {
+        occurance: Option<Occurance>,
+        tokens: Vec<Token>,
+    },
+    Wildcard {
+        occurance: Option<Occurance>,
+        field: Option<String>,
+        pattern: String,
+    },
+    Fuzzy {
+        occurance: Option<Occurance>,
+        field: Option<String>,
+        string: String,
+        distance: u8,
+    },
+    Regex {
+        occurance: Option<Occurance>,
+        field: Option<String>,
+        pattern: String,
+    },
+    Boost {
+        token: Box<Token>,
+        boost: String,
     },
 }
 
+impl Token {
+    pub fn occurance(&self) -> &Option<Occurance> {
+        match self {
+            Token::Text { occurance, .. } => occurance,
+            Token::FilterEquals { occurance, .. } => occurance,
+            Token::FilterExists { occurance, .. } => occurance,
+            Token::FilterIn { occurance, .. } => occurance,
+            Token::FilterRange { occurance, .. } => occurance,
+            Token::Phrase { occurance, .. } => occurance,
+            Token::Group { occurance, .. } => occurance,
+            Token::Wildcard { occurance, .. } => occurance,
+            Token::Fuzzy { occurance, .. } => occurance,
+            Token::Regex { occurance, .. } => occurance,
+            Token::Boost { token, .. } => token.occurance(),
+        }
+    }
+
+    pub fn occurance_mut(&mut self) -> &mut Option<Occurance> {
+        match self {
+            Token::Text { occurance, .. } => occurance,
+            Token::FilterEquals { occurance, .. } => occurance,
+            Token::FilterExists { occurance, .. } => occurance,
+            Token::FilterIn { occurance, .. } => occurance,
+            Token::FilterRange { occurance, .. } => occurance,
+            Token::Phrase { occurance, .. } => occurance,
+            Token::Group { occurance, .. } => occurance,
+            Token::Wildcard { occurance, .. } => occurance,
+            Token::Fuzzy { occurance, .. } => occurance,
+            Token::Regex { occurance, .. } => occurance,
+            Token::Boost { token, .. } => token.occurance_mut(),
+        }
+    }
+}
+
 pub fn parse_query(query: &str) -> QueryParserResult<Vec<Token>> {
+    parse_query_with_positions(query).map(|(tokens, _)| tokens)
+}
+
+// Second value contains start positions of all terms in the same order as they appear in tokens tree.
+pub fn parse_query_with_positions(query: &str) -> QueryParserResult<(Vec<Token>, Vec<usize>)> {
+    let mut cursor = Cursor::new(query);
+    let result = parse_sequence(&mut cursor).and_then(|result| match cursor.peek() {
+        Some(c) => Err(QueryParserError::new(
+            cursor.position(),
+            format!("Unexpected `{}`", c),
+        )),
+        None => Ok((result, cursor.terms().to_vec())),
+    });
+
+    result.map_err(|err| err.with_query(query))
+}
+
+fn parse_sequence(cursor: &mut Cursor) -> QueryParserResult<Vec<Token>> {
     let mut result = Vec::new();
 
-    for part in query.split(char::is_whitespace).filter(|s| !s.is_empty()) {
-        let (occurance, part) = match part.chars().next() {
-            Some('+') => (Some(Occurance::Must), &part[1..]),
-            Some('-') => (Some(Occurance::MustNot), &part[1..]),
-            _ => (None, part),
-        };
+    loop {
+        cursor.skip_whitespace();
+
+        match cursor.peek() {
+            None | Some(')') => break,
+            Some(_) => result.push(parse_or(cursor)?),
+        }
+    }
+
+    // Single group without occurance matches exactly the same documents as its own tokens.
+    match result.as_slice() {
+        [Token::Group {
+            occurance: None,
+            tokens,
+        }] => Ok(tokens.clone()),
+        _ => Ok(result),
+    }
+}
+
+fn parse_or(cursor: &mut Cursor) -> QueryParserResult<Token> {
+    let mut tokens = vec![parse_and(cursor)?];
+
+    while cursor.consume_keyword("OR") {
+        tokens.push(parse_and(cursor)?);
+    }
+
+    Ok(as_group(tokens))
+}
+
+fn parse_and(cursor: &mut Cursor) -> QueryParserResult<Token> {
+    let mut tokens = vec![parse_unary(cursor)?];
+
+    while cursor.consume_keyword("AND") {
+        tokens.push(parse_unary(cursor)?);
+    }
+
+    if tokens.len() > 1 {
+        for token in &mut tokens {
+            let occurance = token.occurance_mut();
+
+            if occurance.is_none() {
+                *occurance = Some(Occurance::Must);
+            }
+        }
+    }
+
+    Ok(as_group(tokens))
+}
+
+fn parse_unary(cursor: &mut Cursor) -> QueryParserResult<Token> {
+    if cursor.consume_keyword("NOT") {
+        let mut token = parse_unary(cursor)?;
+
+        *token.occurance_mut() = Some(Occurance::MustNot);
+
+        return Ok(token);
+    }
+
+    let occurance = if cursor.consume('+') {
+        Some(Occurance::Must)
+    } else if cursor.consume('-') {
+        Some(Occurance::MustNot)
+    } else {
+        None
+    };
+
+    let token = match cursor.peek() {
+        Some('(') => parse_group(cursor, occurance)?,
+        Some(c) if !is_term_end(c) => {
+            cursor.mark_term();
+
+            parse_term(cursor, occurance)?
+        }
+        _ => {
+            return Err(QueryParserError::new(
+                cursor.position(),
+                "Expected search term",
+            ))
+        }
+    };
+
+    if cursor.consume('^') {
+        parse_boost(cursor, token)
+    } else {
+        Ok(token)
+    }
+}
+
+fn parse_group(cursor: &mut Cursor, occurance: Option<Occurance>) -> QueryParserResult<Token> {
+    let position = cursor.position();
 
-        let token = if let Some(index) = part.find(':') {
-            let field = &part[..index];
-            let value = &part[index + 1..];
+    cursor.next_char();
 
-            parse_filter(occurance, field, value)?
+    let tokens = parse_sequence(cursor)?;
+
+    if !cursor.consume(')') {
+        Err(QueryParserError::new(
+            cursor.position(),
+            "Group is not closed by `)`",
+        ))
+    } else if tokens.is_empty() {
+        Err(QueryParserError::new(
+            position,
+            "Group does not contain any terms",
+        ))
+    } else {
+        Ok(Token::Group { occurance, tokens })
+    }
+}
+
+fn parse_boost(cursor: &mut Cursor, token: Token) -> QueryParserResult<Token> {
+    let position = cursor.position();
+    let boost = cursor.read_while(|c| !is_term_end(c));
+
+    match boost.parse::<f32>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok(Token::Boost {
+            token: Box::new(token),
+            boost,
+        }),
+        _ => Err(QueryParserError::new(
+            position,
+            format!("Boost `{}` must be a non-negative number", boost),
+        )),
+    }
+}
+
+fn parse_term(cursor: &mut Cursor, occurance: Option<Occurance>) -> QueryParserResult<Token> {
+    match cursor.peek() {
+        Some('"') => return parse_phrase(cursor, occurance, None),
+        Some('/') => return parse_regex(cursor, occurance, None),
+        _ => {}
+    }
+
+    let position = cursor.position();
+    let (part, part_escaped) = cursor.read_escaped(|c| is_term_end(c) || c == ':');
+
+    if cursor.consume(':') {
+        let position = cursor.position();
+
+        match cursor.peek() {
+            Some('"') => return parse_phrase(cursor, occurance, Some(part)),
+            Some('/') => return parse_regex(cursor, occurance, Some(part)),
+            Some('(') => return parse_set(cursor, occurance, part),
+            _ => {}
+        }
+
+        let (value, escaped) = cursor.read_escaped(is_term_end);
+
+        if escaped {
+            Ok(Token::FilterEquals {
+                occurance,
+                field: part,
+                value,
+            })
         } else {
-            parse_text(occurance, part)?
-        };
+            parse_filter(occurance, &part, &value, position)
+        }
+    } else if part_escaped {
+        parse_literal(occurance, &part, position)
+    } else {
+        parse_text(occurance, &part, position)
+    }
+}
+
+fn parse_phrase(
+    cursor: &mut Cursor,
+    occurance: Option<Occurance>,
+    field: Option<String>,
+) -> QueryParserResult<Token> {
+    let position = cursor.position();
+
+    cursor.next_char();
+
+    let text = cursor
+        .read_quoted('"')
+        .ok_or_else(|| QueryParserError::new(position, "Phrase is not terminated by `\"`"))?;
+    let slop = if cursor.consume('~') {
+        let position = cursor.position();
+
+        Some(parse_slop(
+            &cursor.read_while(|c| !is_term_end(c)),
+            position,
+        )?)
+    } else {
+        None
+    };
+
+    if let Some(c) = cursor.peek().filter(|&c| !is_term_end(c)) {
+        return Err(QueryParserError::new(
+            cursor.position(),
+            format!("Unexpected `{}` after phrase", c),
+        ));
+    }
+
+    match (field, slop) {
+        (Some(field), None) => Ok(Token::FilterEquals {
+            occurance,
+            field,
+            value: text,
+        }),
+        (field, slop) => {
+            let strings = as_words(&text);
+
+            if strings.is_empty() {
+                return Err(QueryParserError::new(
+                    position,
+                    "Phrase does not contain any words",
+                ));
+            }
+
+            Ok(Token::Phrase {
+                occurance,
+                field,
+                strings,
+                slop: slop.unwrap_or(0),
+            })
+        }
+    }
+}
+
+fn parse_regex(
+    cursor: &mut Cursor,
+    occurance: Option<Occurance>,
+    field: Option<String>,
+) -> QueryParserResult<Token> {
+    let position = cursor.position();
+
+    cursor.next_char();
+
+    let mut pattern = String::new();
+
+    loop {
+        match cursor.next_char() {
+            Some('/') => break,
+            Some('\\') => match cursor.next_char() {
+                Some('/') => pattern.push('/'),
+                Some(c) => {
+                    pattern.push('\\');
+                    pattern.push(c);
+                }
+                None => pattern.push('\\'),
+            },
+            Some(c) => pattern.push(c),
+            None => {
+                return Err(QueryParserError::new(
+                    position,
+                    "Regular expression is not terminated by `/`",
+                ))
+            }
+        }
+    }
+
+    if let Err(err) = Regex::new(&pattern) {
+        return Err(QueryParserError::new(
+            position,
+            format!("Invalid regular expression `{}` - {}", pattern, err),
+        ));
+    }
+
+    if let Ok(hir) = RegexParser::new().parse(&pattern) {
+        if !can_match_term(&hir) {
+            return Err(QueryParserError::new(
+                position,
+                format!(
+                    "Regular expression `{}` never matches, index terms contain only lowercase letters and digits",
+                    pattern
+                ),
+            ));
+        }
+    }
+
+    match cursor.peek() {
+        Some(c) if !is_term_end(c) => Err(QueryParserError::new(
+            cursor.position(),
+            format!("Unexpected `{}` after regular expression", c),
+        )),
+        _ => Ok(Token::Regex {
+            occurance,
+            field,
+            pattern,
+        }),
+    }
+}
+
+// Text is split to lowercase words on indexing, so literal separators and uppercase letters are never found in terms.
+fn can_match_term(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Literal(Literal::Unicode(c)) => c.is_alphanumeric() && !c.is_uppercase(),
+        HirKind::Repetition(repetition) => match repetition.kind {
+            RepetitionKind::ZeroOrOne
+            | RepetitionKind::ZeroOrMore
+            | RepetitionKind::Range(RepetitionRange::Exactly(0))
+            | RepetitionKind::Range(RepetitionRange::AtLeast(0))
+            | RepetitionKind::Range(RepetitionRange::Bounded(0, _)) => true,
+            _ => can_match_term(&repetition.hir),
+        },
+        HirKind::Group(group) => can_match_term(&group.hir),
+        HirKind::Concat(hirs) => hirs.iter().all(can_match_term),
+        HirKind::Alternation(hirs) => hirs.iter().any(can_match_term),
+        _ => true,
+    }
+}
+
+fn parse_set(
+    cursor: &mut Cursor,
+    occurance: Option<Occurance>,
+    field: String,
+) -> QueryParserResult<Token> {
+    let position = cursor.position();
+
+    cursor.next_char();
+
+    let mut values = Vec::new();
+
+    loop {
+        let (value, _) = cursor.read_escaped(|c| c == '|' || c == ',' || c == ')');
+        let value = value.trim();
+
+        if !value.is_empty() {
+            values.push(value.into());
+        }
+
+        match cursor.next_char() {
+            Some(')') => break,
+            Some(_) => continue,
+            None => {
+                return Err(QueryParserError::new(
+                    position,
+                    "Value set is not closed by `)`",
+                ))
+            }
+        }
+    }
 
-        result.push(token);
+    if values.is_empty() {
+        return Err(QueryParserError::new(
+            position,
+            "Value set does not contain any values",
+        ));
     }
 
-    Ok(result)
+    match cursor.peek() {
+        Some(c) if !is_term_end(c) => Err(QueryParserError::new(
+            cursor.position(),
+            format!("Unexpected `{}` after value set", c),
+        )),
+        _ => Ok(Token::FilterIn {
+            occurance,
+            field,
+            values,
+        }),
+    }
+}
+
+fn parse_slop(value: &str, position: usize) -> QueryParserResult<u32> {
+    value.parse().map_err(|_| {
+        QueryParserError::new(position, format!("Phrase slop `{}` is not a number", value))
+    })
 }
 
 fn parse_filter(
     occurance: Option<Occurance>,
     field: &str,
     value: &str,
+    position: usize,
 ) -> QueryParserResult<Token> {
+    match (field, value) {
+        ("has", _) => return parse_exists(occurance, value, true, position),
+        ("missing", _) => return parse_exists(occurance, value, false, position),
+        (_, "*") => return parse_exists(occurance, field, true, position),
+        _ => {}
+    }
+
+    if is_wildcard(value) {
+        return parse_wildcard(occurance, Some(field), value, position);
+    }
+
+    if let Some(index) = fuzzy_index(value) {
+        return parse_fuzzy(
+            occurance,
+            Some(field),
+            &value[..index],
+            &value[index + 1..],
+            position,
+        );
+    }
+
     let field = field.into();
+    // Values which only look like ranges are compared as text, builder rejects them on numeric fields.
+    let comparison = [">=", "<=", ">", "<"]
+        .iter()
+        .find(|operator| value.starts_with(*operator) && is_number(&value[operator.len()..]));
+
+    if let Some(&operator) = comparison {
+        let bound = parse_bound(&value[operator.len()..], operator.ends_with('='), position)?;
+        let (left_bound, right_bound) = if operator.starts_with('>') {
+            (bound, RangeBound::Unbounded)
+        } else {
+            (RangeBound::Unbounded, bound)
+        };
 
-    if let Some(index) = value.find("..") {
-        let left_bound = &value[..index];
-        let right_bound = &value[index + 2..];
+        return Ok(Token::FilterRange {
+            occurance,
+            field,
+            left_bound,
+            right_bound,
+        });
+    }
+
+    let (left_inclusive, range, right_inclusive) =
+        match (value.chars().next(), value.chars().last()) {
+            (Some(left @ '['), Some(right @ ']'))
+            | (Some(left @ '['), Some(right @ '}'))
+            | (Some(left @ '{'), Some(right @ ']'))
+            | (Some(left @ '{'), Some(right @ '}'))
+                if value.len() > 1 && value.contains("..") =>
+            {
+                (left == '[', &value[1..value.len() - 1], right == ']')
+            }
+            (Some('['), _) | (Some('{'), _) if value.contains("..") => {
+                return Err(QueryParserError::new(
+                    position,
+                    format!("Range `{}` is not closed by `]` or `}}`", value),
+                ))
+            }
+            _ => (true, value, true),
+        };
+
+    if let Some(index) = range.find("..") {
+        let left_bound = &range[..index];
+        let right_bound = &range[index + 2..];
 
         Ok(Token::FilterRange {
             occurance,
             field,
-            left_bound: parse_number(left_bound)?,
-            right_bound: parse_number(right_bound)?,
+            left_bound: parse_bound(left_bound, left_inclusive, position)?,
+            right_bound: parse_bound(right_bound, right_inclusive, position)?,
         })
     } else {
         Ok(Token::FilterEquals {
@@ -79,25 +591,164 @@ fn parse_filter(
     }
 }
 
-fn parse_number(value: &str) -> QueryParserResult<Option<String>> {
+fn parse_exists(
+    occurance: Option<Occurance>,
+    field: &str,
+    exists: bool,
+    position: usize,
+) -> QueryParserResult<Token> {
+    if field.is_empty() {
+        Err(QueryParserError::new(position, "Field name is empty"))
+    } else {
+        Ok(Token::FilterExists {
+            occurance,
+            field: field.into(),
+            exists,
+        })
+    }
+}
+
+fn parse_bound(value: &str, inclusive: bool, position: usize) -> QueryParserResult<RangeBound> {
+    match parse_number(value, position)? {
+        Some(value) if inclusive => Ok(RangeBound::Included(value)),
+        Some(value) => Ok(RangeBound::Excluded(value)),
+        None => Ok(RangeBound::Unbounded),
+    }
+}
+
+fn parse_number(value: &str, position: usize) -> QueryParserResult<Option<String>> {
     if value.is_empty() {
         Ok(None)
-    } else if value.chars().all(char::is_numeric) {
+    } else if is_number(value) {
         Ok(Some(value.into()))
     } else {
-        Err(QueryParserError::new(format!(
-            "Value `{}` is not a number",
-            value
-        )))
+        Err(QueryParserError::new(
+            position,
+            format!("Value `{}` is not a number", value),
+        ))
+    }
+}
+
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+
+    !digits.is_empty() && digits.chars().all(char::is_numeric)
+}
+
+fn parse_wildcard(
+    occurance: Option<Occurance>,
+    field: Option<&str>,
+    value: &str,
+    position: usize,
+) -> QueryParserResult<Token> {
+    if value
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '*' || c == '?')
+    {
+        Ok(Token::Wildcard {
+            occurance,
+            field: field.map(String::from),
+            pattern: value.into(),
+        })
+    } else {
+        Err(QueryParserError::new(
+            position,
+            format!(
+                "Wildcard `{}` must contain only letters, digits, `*` and `?`, other values must be quoted",
+                value
+            ),
+        ))
+    }
+}
+
+fn parse_fuzzy(
+    occurance: Option<Occurance>,
+    field: Option<&str>,
+    value: &str,
+    distance: &str,
+    position: usize,
+) -> QueryParserResult<Token> {
+    if value.is_empty() || !value.chars().all(char::is_alphanumeric) {
+        return Err(QueryParserError::new(
+            position,
+            format!(
+                "Fuzzy term `{}` must contain only letters and digits",
+                value
+            ),
+        ));
+    }
+
+    match distance.parse() {
+        Ok(distance) if distance <= MAX_FUZZY_DISTANCE => Ok(Token::Fuzzy {
+            occurance,
+            field: field.map(String::from),
+            string: value.into(),
+            distance,
+        }),
+        _ => Err(QueryParserError::new(
+            position,
+            format!(
+                "Fuzzy distance `{}` must be a number from 0 to {}",
+                distance, MAX_FUZZY_DISTANCE
+            ),
+        )),
     }
 }
 
-fn parse_text(occurance: Option<Occurance>, value: &str) -> QueryParserResult<Token> {
+fn parse_text(
+    occurance: Option<Occurance>,
+    value: &str,
+    position: usize,
+) -> QueryParserResult<Token> {
+    if is_wildcard(value) {
+        return parse_wildcard(occurance, None, value, position);
+    }
+
+    if let Some(index) = fuzzy_index(value) {
+        return parse_fuzzy(
+            occurance,
+            None,
+            &value[..index],
+            &value[index + 1..],
+            position,
+        );
+    }
+
     let strings = as_strings(value);
 
     Ok(Token::Text { occurance, strings })
 }
 
+fn parse_literal(
+    occurance: Option<Occurance>,
+    value: &str,
+    position: usize,
+) -> QueryParserResult<Token> {
+    let strings = as_words(value);
+
+    match strings.len() {
+        0 => Err(QueryParserError::new(
+            position,
+            format!("Value `{}` does not contain any words", value),
+        )),
+        1 => Ok(Token::Text { occurance, strings }),
+        _ => Ok(Token::Phrase {
+            occurance,
+            field: None,
+            strings,
+            slop: 0,
+        }),
+    }
+}
+
+fn as_words(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 fn as_strings(value: &str) -> Vec<String> {
     value
         .split('_')
@@ -106,10 +757,39 @@ fn as_strings(value: &str) -> Vec<String> {
         .collect()
 }
 
+fn as_group(mut tokens: Vec<Token>) -> Token {
+    if tokens.len() == 1 {
+        tokens.remove(0)
+    } else {
+        Token::Group {
+            occurance: None,
+            tokens,
+        }
+    }
+}
+
+// Only single word can be followed by distance, other values like paths or URLs can contain `~`.
+fn fuzzy_index(value: &str) -> Option<usize> {
+    value
+        .rfind('~')
+        .filter(|&index| index > 0 && value[..index].chars().all(char::is_alphanumeric))
+}
+
+fn is_wildcard(value: &str) -> bool {
+    value.contains(['*', '?'])
+}
+
+fn is_term_end(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')' || c == '^'
+}
+
 #[cfg(test)]
 mod tests {
     use super::parse_query;
+    use super::parse_query_with_positions;
     use super::Occurance;
+    use super::RangeBound;
+    use super::StructuredQuery;
     use super::Token;
 
     #[test]
@@ -207,8 +887,8 @@ mod tests {
             vec![Token::FilterRange {
                 occurance: None,
                 field: "field".into(),
-                left_bound: Some("10".into()),
-                right_bound: None,
+                left_bound: RangeBound::Included("10".into()),
+                right_bound: RangeBound::Unbounded,
             }]
         );
     }
@@ -223,8 +903,8 @@ mod tests {
             vec![Token::FilterRange {
                 occurance: None,
                 field: "field".into(),
-                left_bound: None,
-                right_bound: Some("20".into()),
+                left_bound: RangeBound::Unbounded,
+                right_bound: RangeBound::Included("20".into()),
             }]
         );
     }
@@ -239,8 +919,8 @@ mod tests {
             vec![Token::FilterRange {
                 occurance: None,
                 field: "field".into(),
-                left_bound: Some("10".into()),
-                right_bound: Some("20".into()),
+                left_bound: RangeBound::Included("10".into()),
+                right_bound: RangeBound::Included("20".into()),
             }]
         );
     }
@@ -258,4 +938,914 @@ mod tests {
 
         assert_eq!(tokens.is_err(), true);
     }
+
+    #[test]
+    fn should_parse_phrase() {
+        let tokens = parse_query("\"connection refused\"");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::Phrase {
+                occurance: None,
+                field: None,
+                strings: ["connection", "refused"]
+                    .iter()
+                    .cloned()
+                    .map(String::from)
+                    .collect(),
+                slop: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_phrase_with_slop() {
+        let tokens = parse_query("+\"connection refused\"~2 test");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Phrase {
+                    occurance: Some(Occurance::Must),
+                    field: None,
+                    strings: ["connection", "refused"]
+                        .iter()
+                        .cloned()
+                        .map(String::from)
+                        .collect(),
+                    slop: 2,
+                },
+                Token::Text {
+                    occurance: None,
+                    strings: ["test"].iter().cloned().map(String::from).collect(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_phrase() {
+        let tokens = parse_query("field:\"test value\"~1");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::Phrase {
+                occurance: None,
+                field: Some("field".into()),
+                strings: ["test", "value"]
+                    .iter()
+                    .cloned()
+                    .map(String::from)
+                    .collect(),
+                slop: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_phrase_not_terminated() {
+        let tokens = parse_query("\"test value");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_phrase_slop_not_number() {
+        let tokens = parse_query("\"test value\"~a");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_and_or_operators() {
+        let tokens = parse_query("(level:error OR level:fatal) AND -timeout");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Group {
+                    occurance: Some(Occurance::Must),
+                    tokens: vec![
+                        Token::FilterEquals {
+                            occurance: None,
+                            field: "level".into(),
+                            value: "error".into(),
+                        },
+                        Token::FilterEquals {
+                            occurance: None,
+                            field: "level".into(),
+                            value: "fatal".into(),
+                        }
+                    ],
+                },
+                Token::Text {
+                    occurance: Some(Occurance::MustNot),
+                    strings: ["timeout"].iter().cloned().map(String::from).collect(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_and_before_or() {
+        let tokens = parse_query("a OR b AND c");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Text {
+                    occurance: None,
+                    strings: ["a"].iter().cloned().map(String::from).collect(),
+                },
+                Token::Group {
+                    occurance: None,
+                    tokens: vec![
+                        Token::Text {
+                            occurance: Some(Occurance::Must),
+                            strings: ["b"].iter().cloned().map(String::from).collect(),
+                        },
+                        Token::Text {
+                            occurance: Some(Occurance::Must),
+                            strings: ["c"].iter().cloned().map(String::from).collect(),
+                        }
+                    ],
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_not_operator() {
+        let tokens = parse_query("NOT test");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::Text {
+                occurance: Some(Occurance::MustNot),
+                strings: ["test"].iter().cloned().map(String::from).collect(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_nested_groups() {
+        let tokens = parse_query("+(test (line OR -word))");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::Group {
+                occurance: Some(Occurance::Must),
+                tokens: vec![
+                    Token::Text {
+                        occurance: None,
+                        strings: ["test"].iter().cloned().map(String::from).collect(),
+                    },
+                    Token::Group {
+                        occurance: None,
+                        tokens: vec![
+                            Token::Text {
+                                occurance: None,
+                                strings: ["line"].iter().cloned().map(String::from).collect(),
+                            },
+                            Token::Text {
+                                occurance: Some(Occurance::MustNot),
+                                strings: ["word"].iter().cloned().map(String::from).collect(),
+                            }
+                        ],
+                    }
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_group_not_closed() {
+        let tokens = parse_query("(test line");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_group_not_opened() {
+        let tokens = parse_query("test line)");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_operator_without_operand() {
+        let tokens = parse_query("test AND");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_wildcard() {
+        let tokens = parse_query("conn* lev?l");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Wildcard {
+                    occurance: None,
+                    field: None,
+                    pattern: "conn*".into(),
+                },
+                Token::Wildcard {
+                    occurance: None,
+                    field: None,
+                    pattern: "lev?l".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_wildcard() {
+        let tokens = parse_query("-field:te?t*");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::Wildcard {
+                occurance: Some(Occurance::MustNot),
+                field: Some("field".into()),
+                pattern: "te?t*".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_wildcard_contains_separator() {
+        let tokens = parse_query("test_li*");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_fuzzy() {
+        let tokens = parse_query("tset~1 level:eror~2");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Fuzzy {
+                    occurance: None,
+                    field: None,
+                    string: "tset".into(),
+                    distance: 1,
+                },
+                Token::Fuzzy {
+                    occurance: None,
+                    field: Some("level".into()),
+                    string: "eror".into(),
+                    distance: 2,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_fuzzy_distance_too_large() {
+        let tokens = parse_query("test~3");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_fuzzy_distance_not_number() {
+        let tokens = parse_query("test~a");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_filter_with_tilde() {
+        let tokens = parse_query("url:http://h/~user path:~/a~1");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "url".into(),
+                    value: "http://h/~user".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "path".into(),
+                    value: "~/a~1".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_with_question_mark_escaped() {
+        let tokens = parse_query(r#"url:"http://h/x?a=1" url:http://h/y\?b=2"#);
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "url".into(),
+                    value: "http://h/x?a=1".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "url".into(),
+                    value: "http://h/y?b=2".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_filter_with_question_mark_not_quoted() {
+        let tokens = parse_query("url:http://h/x?a=1");
+
+        assert_eq!(tokens.is_err(), true);
+        assert_eq!(
+            tokens.unwrap_err().to_string().contains("must be quoted"),
+            true
+        );
+    }
+
+    #[test]
+    fn should_parse_regex() {
+        let tokens = parse_query("/ord\\d{6}/ message:/a\\/?b/");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Regex {
+                    occurance: None,
+                    field: None,
+                    pattern: "ord\\d{6}".into(),
+                },
+                Token::Regex {
+                    occurance: None,
+                    field: Some("message".into()),
+                    pattern: "a/?b".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_regex_invalid() {
+        let tokens = parse_query("/timeout(/");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_regex_contains_separator() {
+        let tokens = parse_query("/ORD-\\d{6}/");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_regex_contains_uppercase() {
+        let tokens = parse_query("message:/Timeout\\d+/");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_regex_requires_separator() {
+        let tokens = parse_query("message:/(timeout|error)_\\d+/");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_regex_with_optional_separator() {
+        let tokens = parse_query("/ord-?\\d{6}/ /(a-b|ab)/ /x[-_]*y/ /(?i)Ord/");
+
+        assert_eq!(tokens.is_ok(), true);
+    }
+
+    #[test]
+    fn should_fail_when_regex_not_terminated() {
+        let tokens = parse_query("/timeout");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_filter_field_negative() {
+        let tokens = parse_query("field:-5..-1");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::FilterRange {
+                occurance: None,
+                field: "field".into(),
+                left_bound: RangeBound::Included("-5".into()),
+                right_bound: RangeBound::Included("-1".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_comparison() {
+        let tokens = parse_query("field:>10 field:<=-20");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterRange {
+                    occurance: None,
+                    field: "field".into(),
+                    left_bound: RangeBound::Excluded("10".into()),
+                    right_bound: RangeBound::Unbounded,
+                },
+                Token::FilterRange {
+                    occurance: None,
+                    field: "field".into(),
+                    left_bound: RangeBound::Unbounded,
+                    right_bound: RangeBound::Included("-20".into()),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_exclusive() {
+        let tokens = parse_query("field:{10..20] field:[..20}");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterRange {
+                    occurance: None,
+                    field: "field".into(),
+                    left_bound: RangeBound::Excluded("10".into()),
+                    right_bound: RangeBound::Included("20".into()),
+                },
+                Token::FilterRange {
+                    occurance: None,
+                    field: "field".into(),
+                    left_bound: RangeBound::Unbounded,
+                    right_bound: RangeBound::Excluded("20".into()),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_comparison_without_number() {
+        let tokens = parse_query("field:>= note:>foo");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "field".into(),
+                    value: ">=".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "note".into(),
+                    value: ">foo".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_brackets_without_range() {
+        let tokens = parse_query("message:[ERROR] tag:{x} tag:[x");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "message".into(),
+                    value: "[ERROR]".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "tag".into(),
+                    value: "{x}".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "tag".into(),
+                    value: "[x".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_range_not_closed() {
+        let tokens = parse_query("field:{10..20");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_filter_field_set() {
+        let tokens = parse_query("-id:(10|11|42) level:(error, fatal)");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterIn {
+                    occurance: Some(Occurance::MustNot),
+                    field: "id".into(),
+                    values: ["10", "11", "42"]
+                        .iter()
+                        .cloned()
+                        .map(String::from)
+                        .collect(),
+                },
+                Token::FilterIn {
+                    occurance: None,
+                    field: "level".into(),
+                    values: ["error", "fatal"]
+                        .iter()
+                        .cloned()
+                        .map(String::from)
+                        .collect(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_set_empty() {
+        let tokens = parse_query("id:( | )");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_set_not_closed() {
+        let tokens = parse_query("id:(10|11");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_filter_field_exists() {
+        let tokens = parse_query("has:level missing:rev -field:*");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterExists {
+                    occurance: None,
+                    field: "level".into(),
+                    exists: true,
+                },
+                Token::FilterExists {
+                    occurance: None,
+                    field: "rev".into(),
+                    exists: false,
+                },
+                Token::FilterExists {
+                    occurance: Some(Occurance::MustNot),
+                    field: "field".into(),
+                    exists: true,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_exists_without_field() {
+        let tokens = parse_query("missing:");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_parse_filter_field_quoted() {
+        let tokens = parse_query(r#"path:"C:\tmp\a b" name:"say \"hi\"""#);
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "path".into(),
+                    value: r"C:\tmp\a b".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "name".into(),
+                    value: r#"say "hi""#.into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_with_colons() {
+        let tokens = parse_query("url:http://host:8080/x");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::FilterEquals {
+                occurance: None,
+                field: "url".into(),
+                value: "http://host:8080/x".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_filter_field_escaped() {
+        let tokens = parse_query(r"rev:10\.\.20 name:a\ b\* addr:\(1\|2\)");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "rev".into(),
+                    value: "10..20".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "name".into(),
+                    value: "a b*".into(),
+                },
+                Token::FilterEquals {
+                    occurance: None,
+                    field: "addr".into(),
+                    value: "(1|2)".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_escaped_text() {
+        let tokens = parse_query(r"host\:8080 \-test\*");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Phrase {
+                    occurance: None,
+                    field: None,
+                    strings: ["host", "8080"].iter().cloned().map(String::from).collect(),
+                    slop: 0,
+                },
+                Token::Text {
+                    occurance: None,
+                    strings: ["test"].iter().cloned().map(String::from).collect(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_escaped_set_values() {
+        let tokens = parse_query(r"addr:(a\|b|c\)d)");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![Token::FilterIn {
+                occurance: None,
+                field: "addr".into(),
+                values: ["a|b", "c)d"].iter().cloned().map(String::from).collect(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_boost() {
+        let tokens = parse_query("+test^2 field:value^0.5 (a b)^3");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Boost {
+                    token: Box::new(Token::Text {
+                        occurance: Some(Occurance::Must),
+                        strings: vec!["test".into()],
+                    }),
+                    boost: "2".into(),
+                },
+                Token::Boost {
+                    token: Box::new(Token::FilterEquals {
+                        occurance: None,
+                        field: "field".into(),
+                        value: "value".into(),
+                    }),
+                    boost: "0.5".into(),
+                },
+                Token::Boost {
+                    token: Box::new(Token::Group {
+                        occurance: None,
+                        tokens: vec![
+                            Token::Text {
+                                occurance: None,
+                                strings: vec!["a".into()],
+                            },
+                            Token::Text {
+                                occurance: None,
+                                strings: vec!["b".into()],
+                            },
+                        ],
+                    }),
+                    boost: "3".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_boost_with_operators() {
+        let tokens = parse_query("\"test line\"~1^2 AND NOT value~1^4");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(
+            tokens.unwrap(),
+            vec![
+                Token::Boost {
+                    token: Box::new(Token::Phrase {
+                        occurance: Some(Occurance::Must),
+                        field: None,
+                        strings: vec!["test".into(), "line".into()],
+                        slop: 1,
+                    }),
+                    boost: "2".into(),
+                },
+                Token::Boost {
+                    token: Box::new(Token::Fuzzy {
+                        occurance: Some(Occurance::MustNot),
+                        field: None,
+                        string: "value".into(),
+                        distance: 1,
+                    }),
+                    boost: "4".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_when_boost_not_number() {
+        let tokens = parse_query("test^a");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_fail_when_boost_negative() {
+        let tokens = parse_query("test^-1");
+
+        assert_eq!(tokens.is_err(), true);
+    }
+
+    #[test]
+    fn should_convert_structured_query() {
+        let query: StructuredQuery = serde_json::from_str(
+            r#"{
+                "bool": {
+                    "must": [
+                        { "term": { "field": "level", "value": "error" } },
+                        { "range": { "field": "rev", "gt": -5, "lte": 10 } }
+                    ],
+                    "should": [
+                        { "phrase": { "text": "connection refused", "slop": 1 } },
+                        { "prefix": { "field": "message", "value": "time" } }
+                    ],
+                    "must_not": [{ "term": { "value": 42 } }]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.to_tokens(),
+            vec![
+                Token::FilterEquals {
+                    occurance: Some(Occurance::Must),
+                    field: "level".into(),
+                    value: "error".into(),
+                },
+                Token::FilterRange {
+                    occurance: Some(Occurance::Must),
+                    field: "rev".into(),
+                    left_bound: RangeBound::Excluded("-5".into()),
+                    right_bound: RangeBound::Included("10".into()),
+                },
+                Token::Phrase {
+                    occurance: None,
+                    field: None,
+                    strings: vec!["connection".into(), "refused".into()],
+                    slop: 1,
+                },
+                Token::Wildcard {
+                    occurance: None,
+                    field: Some("message".into()),
+                    pattern: "time*".into(),
+                },
+                Token::Text {
+                    occurance: Some(Occurance::MustNot),
+                    strings: vec!["42".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_convert_nested_structured_query() {
+        let query: StructuredQuery = serde_json::from_str(
+            r#"{
+                "bool": {
+                    "must_not": [{
+                        "bool": {
+                            "should": [
+                                { "term": { "field": "level", "value": "info" } },
+                                { "term": { "field": "level", "value": "debug" } }
+                            ]
+                        }
+                    }]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.to_tokens(),
+            vec![Token::Group {
+                occurance: Some(Occurance::MustNot),
+                tokens: vec![
+                    Token::FilterEquals {
+                        occurance: None,
+                        field: "level".into(),
+                        value: "info".into(),
+                    },
+                    Token::FilterEquals {
+                        occurance: None,
+                        field: "level".into(),
+                        value: "debug".into(),
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn should_report_term_positions() {
+        let tokens = parse_query_with_positions("a (+b:1 OR \"c d\")^2 NOT e");
+
+        assert_eq!(tokens.is_ok(), true);
+        assert_eq!(tokens.unwrap().1, vec![0, 4, 11, 24]);
+    }
+
+    #[test]
+    fn should_report_error_position() {
+        let error = parse_query("test field:a..20").unwrap_err();
+
+        assert_eq!(error.position(), 11);
+        assert_eq!(
+            error.to_string(),
+            "Value `a` is not a number at position 11\ntest field:a..20\n           ^"
+        );
+    }
+
+    #[test]
+    fn should_report_unclosed_group_position() {
+        let error = parse_query("(test line").unwrap_err();
+
+        assert_eq!(error.position(), 10);
+    }
 }
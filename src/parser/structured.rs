@@ -0,0 +1,162 @@
+use super::as_words;
+use super::Occurance;
+use super::RangeBound;
+use super::Token;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredQuery {
+    Bool {
+        #[serde(default)]
+        must: Vec<StructuredQuery>,
+        #[serde(default)]
+        should: Vec<StructuredQuery>,
+        #[serde(default)]
+        must_not: Vec<StructuredQuery>,
+    },
+    Term {
+        field: Option<String>,
+        value: StructuredValue,
+    },
+    Range {
+        field: String,
+        gt: Option<StructuredValue>,
+        gte: Option<StructuredValue>,
+        lt: Option<StructuredValue>,
+        lte: Option<StructuredValue>,
+    },
+    Phrase {
+        field: Option<String>,
+        text: String,
+        #[serde(default)]
+        slop: u32,
+    },
+    Prefix {
+        field: Option<String>,
+        value: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StructuredValue {
+    Signed(i64),
+    Unsigned(u64),
+    String(String),
+}
+
+impl StructuredQuery {
+    pub fn to_tokens(&self) -> Vec<Token> {
+        // Top level boolean query matches exactly the same documents as its own clauses.
+        match self.to_token() {
+            Token::Group {
+                occurance: None,
+                tokens,
+            } => tokens,
+            token => vec![token],
+        }
+    }
+
+    fn to_token(&self) -> Token {
+        match self {
+            StructuredQuery::Bool {
+                must,
+                should,
+                must_not,
+            } => {
+                let mut tokens = Vec::new();
+
+                for (occurance, queries) in &[
+                    (Some(Occurance::Must), must),
+                    (None, should),
+                    (Some(Occurance::MustNot), must_not),
+                ] {
+                    for query in queries.iter() {
+                        let mut token = query.to_token();
+
+                        *token.occurance_mut() = occurance.clone();
+                        tokens.push(token);
+                    }
+                }
+
+                Token::Group {
+                    occurance: None,
+                    tokens,
+                }
+            }
+            StructuredQuery::Term {
+                field: Some(field),
+                value,
+            } => Token::FilterEquals {
+                occurance: None,
+                field: field.clone(),
+                value: value.to_string(),
+            },
+            StructuredQuery::Term { field: None, value } => {
+                let strings = as_words(&value.to_string());
+
+                if strings.len() == 1 {
+                    Token::Text {
+                        occurance: None,
+                        strings,
+                    }
+                } else {
+                    Token::Phrase {
+                        occurance: None,
+                        field: None,
+                        strings,
+                        slop: 0,
+                    }
+                }
+            }
+            StructuredQuery::Range {
+                field,
+                gt,
+                gte,
+                lt,
+                lte,
+            } => Token::FilterRange {
+                occurance: None,
+                field: field.clone(),
+                left_bound: to_bound(gt, gte),
+                right_bound: to_bound(lt, lte),
+            },
+            StructuredQuery::Phrase { field, text, slop } => Token::Phrase {
+                occurance: None,
+                field: field.clone(),
+                strings: as_words(text),
+                slop: *slop,
+            },
+            StructuredQuery::Prefix { field, value } => Token::Wildcard {
+                occurance: None,
+                field: field.clone(),
+                pattern: format!("{}*", value),
+            },
+        }
+    }
+}
+
+impl Display for StructuredValue {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            StructuredValue::Signed(value) => write!(f, "{}", value),
+            StructuredValue::Unsigned(value) => write!(f, "{}", value),
+            StructuredValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+// Exclusive bound takes precedence when both bounds are given.
+fn to_bound(
+    exclusive: &Option<StructuredValue>,
+    inclusive: &Option<StructuredValue>,
+) -> RangeBound {
+    match (exclusive, inclusive) {
+        (Some(value), _) => RangeBound::Excluded(value.to_string()),
+        (None, Some(value)) => RangeBound::Included(value.to_string()),
+        (None, None) => RangeBound::Unbounded,
+    }
+}
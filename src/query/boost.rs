@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+use tantivy::query::Query;
+use tantivy::query::Scorer;
+use tantivy::query::Weight;
+use tantivy::DocId;
+use tantivy::DocSet;
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::Searcher;
+use tantivy::SegmentReader;
+use tantivy::SkipResult;
+use tantivy::Term;
+
+// Tantivy does not provide query boosting yet, so scores of the inner query are multiplied here.
+#[derive(Debug)]
+pub struct BoostQuery {
+    query: Box<Query>,
+    boost: Score,
+}
+
+impl BoostQuery {
+    pub fn new(query: Box<Query>, boost: Score) -> BoostQuery {
+        BoostQuery { query, boost }
+    }
+}
+
+impl Clone for BoostQuery {
+    fn clone(&self) -> BoostQuery {
+        BoostQuery {
+            query: self.query.box_clone(),
+            boost: self.boost,
+        }
+    }
+}
+
+impl Query for BoostQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        Ok(Box::new(BoostWeight {
+            weight: self.query.weight(searcher, scoring_enabled)?,
+            boost: self.boost,
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set);
+    }
+}
+
+struct BoostWeight {
+    weight: Box<Weight>,
+    boost: Score,
+}
+
+impl Weight for BoostWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        Ok(Box::new(BoostScorer {
+            scorer: self.weight.scorer(reader)?,
+            boost: self.boost,
+        }))
+    }
+
+    fn count(&self, reader: &SegmentReader) -> Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+struct BoostScorer {
+    scorer: Box<Scorer>,
+    boost: Score,
+}
+
+impl DocSet for BoostScorer {
+    fn advance(&mut self) -> bool {
+        self.scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.scorer.skip_next(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for BoostScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score() * self.boost
+    }
+}
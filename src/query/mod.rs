@@ -0,0 +1,3 @@
+mod boost;
+
+pub use self::boost::BoostQuery;
@@ -1,8 +1,15 @@
 use crate::config::ConfigRef;
 use crate::error::ApplicationError;
 use crate::error::ApplicationResult;
+use crate::handler::ContextHandler;
+use crate::handler::DocumentHandler;
+use crate::handler::ExplainHandler;
+use crate::handler::ExportHandler;
 use crate::handler::FieldsHandler;
+use crate::handler::ParseHandler;
 use crate::handler::SearchHandler;
+use crate::handler::SimilarHandler;
+use crate::handler::SuggestHandler;
 use crate::index::TextIndexRef;
 use crate::options::Options;
 use iron::Iron;
@@ -18,6 +25,13 @@ pub fn start(
     let mut mount = Mount::new();
     mount.mount("/api/v1/fields", FieldsHandler::new(config));
     mount.mount("/api/v1/search", SearchHandler::new(config, text_index));
+    mount.mount("/api/v1/parse", ParseHandler::new(config));
+    mount.mount("/api/v1/export", ExportHandler::new(config, text_index));
+    mount.mount("/api/v1/explain", ExplainHandler::new(config, text_index));
+    mount.mount("/api/v1/similar", SimilarHandler::new(config, text_index));
+    mount.mount("/api/v1/document", DocumentHandler::new(config, text_index));
+    mount.mount("/api/v1/context", ContextHandler::new(config, text_index));
+    mount.mount("/api/v1/suggest", SuggestHandler::new(config, text_index));
     mount.mount("/static", Static::new("public/static"));
     mount.mount("/", Static::new("public"));
 
@@ -3,7 +3,9 @@ use super::error::WorkerResult;
 use crate::config::ConfigRef;
 use crate::config::DataType;
 use crate::config::FieldConfig;
+use crate::index::facet_field_name;
 use crate::index::TextIndexRef;
+use crate::index::PRESENT_FIELD;
 use fallible_iterator::FallibleIterator;
 use postgres::rows::LazyRows;
 use postgres::rows::Row;
@@ -14,6 +16,7 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::thread::sleep;
 use std::time::Duration;
+use tantivy::schema::Facet;
 use tantivy::schema::Field;
 use tantivy::Document;
 use tantivy::IndexWriter;
@@ -120,6 +123,10 @@ fn read_rows(
     mut rows: LazyRows,
 ) -> WorkerResult<Option<i64>> {
     let mut last_key = None;
+    let present_field = schema
+        .get(PRESENT_FIELD)
+        .cloned()
+        .ok_or_else(|| WorkerError::field_not_found(PRESENT_FIELD))?;
 
     while let Some(row) = rows.next().map_err(WorkerError::stream_error)? {
         let mut document = Document::new();
@@ -131,26 +138,61 @@ fn read_rows(
                 .cloned()
                 .ok_or_else(|| WorkerError::field_not_found(name))?;
             let column = field_config.column();
-
-            match field_config.data_type() {
+            let facet_field = if field_config.facet() {
+                let facet_name = facet_field_name(name);
+
+                Some(
+                    schema
+                        .get(&facet_name)
+                        .cloned()
+                        .ok_or_else(|| WorkerError::field_not_found(&facet_name))?,
+                )
+            } else {
+                None
+            };
+            let present_value = match field_config.data_type() {
                 DataType::Int { .. } => {
-                    if let Some(value) = get_value(&row, column)? {
+                    let value = get_value(&row, column)?;
+
+                    if let Some(value) = value {
                         document.add_i64(field, value);
 
                         if column == key_name {
                             last_key = Some(value);
                         }
                     }
+
+                    value.map(|value| value.to_string())
                 }
                 DataType::UInt { .. } => {
-                    if let Some(value) = get_value::<u32>(&row, column)? {
+                    let value = get_value::<u32>(&row, column)?;
+
+                    if let Some(value) = value {
                         document.add_u64(field, u64::from(value));
                     }
+
+                    value.map(|value| value.to_string())
                 }
                 DataType::Text => {
-                    if let Some(value) = get_value::<String>(&row, column)? {
+                    let value = get_value::<String>(&row, column)?;
+
+                    if let Some(value) = &value {
                         document.add_text(field, value.trim());
                     }
+
+                    value.map(|value| value.trim().to_string())
+                }
+            };
+
+            if let Some(value) = present_value {
+                document.add_text(present_field, name);
+
+                // Empty path is the root facet, so empty values are not counted.
+                match facet_field {
+                    Some(facet_field) if !value.is_empty() => {
+                        document.add_facet(facet_field, Facet::from_path(vec![value]));
+                    }
+                    _ => {}
                 }
             }
         }